@@ -1,19 +1,170 @@
+use std::str::FromStr;
+
 use serde::{Deserialize, Serialize};
-use strum::Display;
+use serde_json::Value;
+use strum::{Display, EnumIter};
+
+use crate::app::Mode;
 
-#[derive(Debug, Clone, PartialEq, Eq, Display, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Display, EnumIter, Serialize, Deserialize)]
 pub enum Action {
+    #[default]
     Tick,
     Render,
     Resize(u16, u16),
     Suspend,
     Resume,
     Quit,
+    /// Quit without asking for confirmation.
+    ForceQuit,
+    /// Asks with the prompt before handling the action, e.g.
+    /// `{"Confirm": [{"IncrementBy": 100}, "Add 100?"]}`.
+    Confirm(Box<Action>, String),
+    /// The user answered yes to [`Action::Confirm`], so the action is handled without asking
+    /// again. Only accepted for the action that is waiting for the answer.
+    Confirmed(Box<Action>),
+    /// The user answered no to [`Action::Confirm`], which closes the dialog.
+    CancelConfirm,
     Refresh,
     Error(String),
     Help,
     ClearScreen,
+    OpenCommandPalette,
+    CloseCommandPalette,
+    /// Closes the topmost popup, e.g. the help or the command palette.
+    ClosePopup,
+    SetTickRate(f64),
+    SetFrameRate(f64),
+    ToggleFpsOverlay,
+    /// Switches to the logs tab, or back to the previous tab from there.
+    ToggleLogViewer,
+    NextTab,
+    PrevTab,
+    /// Switches to a tab, e.g. `SelectTab Settings`.
+    SelectTab(Mode),
+    /// Moves the focus to the next component of the current tab.
+    FocusNext,
+    FocusPrev,
+    /// Changes the log filter, e.g. `SetLogLevel debug`.
+    SetLogLevel(String),
+    // Background task actions, see `tasks`
+    TaskProgress(u64, f64),
+    TaskFinished(u64),
+    TaskCancelled(u64),
     // Counter actions
     IncrementBy(i32),
     DecrementBy(i32),
+    /// Sets the count back to zero and the amount back to one.
+    Reset,
+    SetCount(i32),
+    /// Sets the amount the arrow keys move the count by, at least one.
+    SetAmount(i32),
+    /// Moves the count to the target, clamped to the bounds, by the amount at a time in the
+    /// background. Cancelled by another `CountTo` and when switching to another mode.
+    CountTo(i32),
+}
+
+impl Action {
+    /// Whether the action carries parameters, e.g. `IncrementBy(i32)`.
+    pub fn has_parameters(&self) -> bool {
+        !matches!(serde_json::to_value(self), Ok(Value::String(_)))
+    }
+
+    /// Formats the action in command syntax, e.g. `IncrementBy 5` or `Resize 80 24`. Actions that
+    /// wrap another action are formatted as JSON.
+    pub fn to_command(&self) -> String {
+        if let Action::Confirm(..) | Action::Confirmed(..) = self {
+            return serde_json::to_string(self).unwrap_or_else(|_| self.to_string());
+        }
+        let Ok(Value::Object(map)) = serde_json::to_value(self) else {
+            return self.to_string();
+        };
+        let mut command = self.to_string();
+        for value in map.values() {
+            let values = match value {
+                Value::Array(values) => values.clone(),
+                value => vec![value.clone()],
+            };
+            for value in values {
+                command.push(' ');
+                match value {
+                    Value::String(s) => command.push_str(&s),
+                    value => command.push_str(&value.to_string()),
+                }
+            }
+        }
+        command
+    }
+}
+
+/// Parses an action from command syntax (`Quit`, `IncrementBy 5`, `Error some text`) or from its
+/// JSON encoding (`{"IncrementBy": 5}`).
+impl FromStr for Action {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if let Ok(action) = serde_json::from_str(s) {
+            return Ok(action);
+        }
+
+        let (name, rest) = s.split_once(char::is_whitespace).unwrap_or((s, ""));
+        let rest = rest.trim();
+        let parse_value = |raw: &str| {
+            serde_json::from_str(raw).unwrap_or_else(|_| Value::String(raw.to_string()))
+        };
+
+        let mut candidates = vec![];
+        if rest.is_empty() {
+            candidates.push(Value::String(name.to_string()));
+        } else {
+            candidates.push(serde_json::json!({ name: parse_value(rest) }));
+            let values = rest.split_whitespace().map(parse_value).collect::<Vec<_>>();
+            candidates.push(serde_json::json!({ name: values }));
+        }
+        // String parameters are taken verbatim, including when empty or numeric.
+        candidates.push(serde_json::json!({ name: rest }));
+
+        candidates
+            .into_iter()
+            .find_map(|value| serde_json::from_value(value).ok())
+            .ok_or_else(|| format!("Unable to parse action `{s}`"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use strum::IntoEnumIterator;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_command() {
+        assert_eq!("Quit".parse(), Ok(Action::Quit));
+        assert_eq!("IncrementBy 5".parse(), Ok(Action::IncrementBy(5)));
+        assert_eq!("Resize 80 24".parse(), Ok(Action::Resize(80, 24)));
+        assert_eq!(
+            "Error out of range".parse(),
+            Ok(Action::Error("out of range".to_string()))
+        );
+        assert_eq!(r#"{"DecrementBy": 2}"#.parse(), Ok(Action::DecrementBy(2)));
+        assert_eq!(
+            "SelectTab Settings".parse(),
+            Ok(Action::SelectTab(Mode::Settings))
+        );
+        assert!("IncrementBy".parse::<Action>().is_err());
+        assert!("Unknown".parse::<Action>().is_err());
+    }
+
+    #[test]
+    fn test_command_round_trip() {
+        for action in Action::iter() {
+            assert_eq!(action.to_command().parse(), Ok(action));
+        }
+        let action = Action::Error("two words".to_string());
+        assert_eq!(action.to_command().parse(), Ok(action));
+        let action = Action::Confirm(Box::new(Action::IncrementBy(5)), "Add 5?".to_string());
+        assert_eq!(action.to_command().parse(), Ok(action));
+    }
 }
@@ -0,0 +1,225 @@
+//! The bounded queue that carries [`Action`]s to the app loop.
+
+use std::{
+    num::NonZeroUsize,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+
+use color_eyre::{eyre::eyre, Result};
+use tokio::sync::mpsc::{self, error::TrySendError};
+use tracing::warn;
+
+use crate::action::Action;
+
+/// Default capacity of the action and event queues.
+pub const DEFAULT_QUEUE_CAPACITY: NonZeroUsize = NonZeroUsize::new(1024).unwrap();
+
+/// Creates a bounded action queue.
+pub fn channel(capacity: NonZeroUsize) -> (ActionSender, ActionReceiver) {
+    let (tx, rx) = mpsc::channel(capacity.get());
+    let (overflow_tx, overflow_rx) = mpsc::unbounded_channel();
+    let flags = Arc::new(Flags::default());
+    (
+        ActionSender {
+            tx,
+            overflow_tx,
+            flags: flags.clone(),
+        },
+        ActionReceiver {
+            rx,
+            overflow_rx,
+            flags,
+        },
+    )
+}
+
+/// Whether the action controls the app's lifecycle, so that it must not be lost even when the
+/// queue is full.
+fn is_control(action: &Action) -> bool {
+    matches!(
+        action,
+        Action::Quit | Action::ForceQuit | Action::Suspend | Action::Resume
+    )
+}
+
+#[derive(Debug, Default)]
+struct Flags {
+    tick_queued: AtomicBool,
+    render_queued: AtomicBool,
+    /// Set once a full queue has been reported, until an action fits again.
+    full: AtomicBool,
+}
+
+impl Flags {
+    fn queued(&self, action: &Action) -> Option<&AtomicBool> {
+        match action {
+            Action::Tick => Some(&self.tick_queued),
+            Action::Render => Some(&self.render_queued),
+            _ => None,
+        }
+    }
+}
+
+/// Sending half of the action queue.
+///
+/// A [`Action::Tick`] or [`Action::Render`] is dropped while another one is still queued, since
+/// handling it twice in a row has no effect. Quitting, suspending and resuming are never dropped:
+/// when the queue is full they go to an unbounded overflow queue, which is received from first.
+#[derive(Debug, Clone)]
+pub struct ActionSender {
+    tx: mpsc::Sender<Action>,
+    overflow_tx: mpsc::UnboundedSender<Action>,
+    flags: Arc<Flags>,
+}
+
+impl ActionSender {
+    /// Queues an action without waiting. This is what the app loop and components use, since
+    /// waiting for the loop from within it would never finish; if the queue is full, the action is
+    /// dropped and a warning is logged, unless it is a control action.
+    pub fn send(&self, action: Action) -> Result<()> {
+        if self.coalesce(&action) {
+            return Ok(());
+        }
+        match self.tx.try_send(action) {
+            Ok(()) => {
+                self.flags.full.store(false, Ordering::Relaxed);
+                Ok(())
+            }
+            Err(TrySendError::Full(action)) if is_control(&action) => {
+                warn!("Action queue is full, sending {action:?} ahead of it");
+                self.overflow_tx
+                    .send(action)
+                    .map_err(|_| eyre!("Action queue is closed"))
+            }
+            Err(TrySendError::Full(action)) => {
+                self.unmark(&action);
+                if !self.flags.full.swap(true, Ordering::Relaxed) {
+                    warn!(
+                        "Action queue is full ({} actions), dropping {action:?}",
+                        self.tx.max_capacity()
+                    );
+                }
+                Ok(())
+            }
+            Err(TrySendError::Closed(_)) => Err(eyre!("Action queue is closed")),
+        }
+    }
+
+    /// Queues an action, waiting for room if the queue is full. For producers outside the app
+    /// loop, such as command input, so that they slow down instead of losing actions.
+    pub async fn send_wait(&self, action: Action) -> Result<()> {
+        if self.coalesce(&action) {
+            return Ok(());
+        }
+        if self.tx.capacity() == 0 {
+            warn!("Action queue is full, waiting to send {action:?}");
+        }
+        self.tx
+            .send(action)
+            .await
+            .map_err(|_| eyre!("Action queue is closed"))
+    }
+
+    /// Returns whether the action is redundant with one that is already queued.
+    fn coalesce(&self, action: &Action) -> bool {
+        self.flags
+            .queued(action)
+            .is_some_and(|queued| queued.swap(true, Ordering::AcqRel))
+    }
+
+    fn unmark(&self, action: &Action) {
+        if let Some(queued) = self.flags.queued(action) {
+            queued.store(false, Ordering::Release);
+        }
+    }
+}
+
+/// Receiving half of the action queue.
+#[derive(Debug)]
+pub struct ActionReceiver {
+    rx: mpsc::Receiver<Action>,
+    overflow_rx: mpsc::UnboundedReceiver<Action>,
+    flags: Arc<Flags>,
+}
+
+impl ActionReceiver {
+    pub fn try_recv(&mut self) -> Option<Action> {
+        if let Ok(action) = self.overflow_rx.try_recv() {
+            return Some(action);
+        }
+        let action = self.rx.try_recv().ok()?;
+        if let Some(queued) = self.flags.queued(&action) {
+            queued.store(false, Ordering::Release);
+        }
+        Some(action)
+    }
+
+    /// Number of queued actions.
+    pub fn len(&self) -> usize {
+        self.rx.len() + self.overflow_rx.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn drain(rx: &mut ActionReceiver) -> Vec<Action> {
+        std::iter::from_fn(|| rx.try_recv()).collect()
+    }
+
+    #[test]
+    fn test_coalesce_tick_and_render() {
+        let (tx, mut rx) = channel(DEFAULT_QUEUE_CAPACITY);
+        tx.send(Action::Tick).unwrap();
+        tx.send(Action::Render).unwrap();
+        tx.send(Action::Tick).unwrap();
+        tx.send(Action::Quit).unwrap();
+        tx.send(Action::Render).unwrap();
+        assert_eq!(
+            drain(&mut rx),
+            vec![Action::Tick, Action::Render, Action::Quit]
+        );
+
+        tx.send(Action::Tick).unwrap();
+        assert_eq!(drain(&mut rx), vec![Action::Tick]);
+    }
+
+    #[test]
+    fn test_full_queue_drops() {
+        let (tx, mut rx) = channel(NonZeroUsize::new(2).unwrap());
+        for amount in 0..4 {
+            tx.send(Action::IncrementBy(amount)).unwrap();
+        }
+        // Room for a render is not held up by a dropped one.
+        tx.send(Action::Render).unwrap();
+        assert_eq!(
+            drain(&mut rx),
+            vec![Action::IncrementBy(0), Action::IncrementBy(1)]
+        );
+        tx.send(Action::Render).unwrap();
+        assert_eq!(drain(&mut rx), vec![Action::Render]);
+
+        drop(rx);
+        assert!(tx.send(Action::Quit).is_err());
+    }
+
+    #[test]
+    fn test_full_queue_keeps_control_actions() {
+        let (tx, mut rx) = channel(NonZeroUsize::new(1).unwrap());
+        tx.send(Action::IncrementBy(1)).unwrap();
+        tx.send(Action::IncrementBy(2)).unwrap();
+        tx.send(Action::Suspend).unwrap();
+        tx.send(Action::ForceQuit).unwrap();
+        assert_eq!(rx.len(), 3);
+        assert_eq!(
+            drain(&mut rx),
+            vec![Action::Suspend, Action::ForceQuit, Action::IncrementBy(1)]
+        );
+    }
+}
@@ -1,112 +1,356 @@
+use std::{
+    collections::HashMap,
+    num::NonZeroUsize,
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
 use color_eyre::Result;
-use crossterm::event::KeyEvent;
+use crossterm::event::{KeyCode, KeyEvent, KeyEventKind, MouseButton, MouseEvent, MouseEventKind};
 use ratatui::{
-    layout::{Constraint, Direction, Layout},
+    layout::{Constraint, Flex, Layout, Position},
     prelude::Rect,
+    widgets::Clear,
 };
 use serde::{Deserialize, Serialize};
-use tokio::sync::mpsc;
-use tracing::{debug, info};
+use tokio::{
+    sync::{mpsc, oneshot},
+    task::JoinHandle,
+};
+use tracing::{debug, error, info, instrument, trace_span, warn};
 
 use crate::{
     action::Action,
-    components::{counter::Counter, fps::FpsCounter, home::Home, Component},
-    config::Config,
+    action_queue::{self, ActionReceiver, ActionSender, DEFAULT_QUEUE_CAPACITY},
+    commands::{check_command_input, read_command_file, spawn_command_reader},
+    components::{
+        command_palette::CommandPalette, confirm_dialog::ConfirmDialog, counter::Counter,
+        fps::FpsCounter, help::Help, home::Home, keybindings::Keybindings, log_viewer::LogViewer,
+        settings::Settings, status_bar::StatusBar, tab_bar::TabBar, Component,
+    },
+    config::{key_sequence_to_string, Config},
+    control::{remove_socket, spawn_control_server, ControlRequest},
+    crash,
     event::Event,
-    event_handler::EventHandler,
+    event_handler::{EventHandler, Handled},
+    logging,
+    metrics::Metrics,
+    signals::spawn_signal_handler,
     state::AppState,
-    tui::Tui,
+    tasks::TaskManager,
+    tui::{validate_rate, Tui},
 };
 
+/// How often a component may emit the same action within one drain of the action queue before it
+/// is considered part of a cycle and dropped.
+const MAX_REPEATS_PER_DRAIN: usize = 16;
+
+/// Time between the steps of [`Action::CountTo`].
+const COUNT_STEP_INTERVAL: Duration = Duration::from_millis(100);
+
+/// How long background tasks get to wind down after being cancelled on shutdown.
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Where [`App::update_layout`] places a component.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Slot {
+    /// The top line.
+    TabBar,
+    Header,
+    Stats,
+    Main,
+    /// The whole area between the tab bar and the status bar.
+    Full,
+    Left,
+    Right,
+    /// The bottom line.
+    StatusBar,
+    /// Centered over the area between the tab bar and the status bar while the popup for this
+    /// mode is open, see [`Component::popup_size`].
+    Popup(Mode),
+}
+
 struct ComponentWithRect {
     component: Box<dyn Component>,
+    slot: Slot,
+    /// The tab that shows the component, or `None` for all of them.
+    tab: Option<Mode>,
     rect: Rect,
 }
 
+impl ComponentWithRect {
+    /// Whether the component is part of `tab`. Popups are shown on top while they are open.
+    fn is_shown(&self, tab: Mode) -> bool {
+        !matches!(self.slot, Slot::Popup(_)) && self.tab.is_none_or(|shown_in| shown_in == tab)
+    }
+}
+
 pub struct App {
     config: Config,
     tick_rate: f64,
     frame_rate: f64,
+    keyboard_enhancement: bool,
+    inline: Option<u16>,
+    queue_capacity: NonZeroUsize,
+    commands: Option<PathBuf>,
+    command_input: Option<PathBuf>,
+    control_socket: Option<PathBuf>,
+    control_rx: Option<mpsc::Receiver<ControlRequest>>,
+    /// Actions read from the command input, see [`spawn_command_reader`].
+    command_rx: Option<mpsc::Receiver<Action>>,
+    /// Control requests whose actions are queued and will be acknowledged once processed.
+    pending_control_replies: Vec<oneshot::Sender<()>>,
     components_with_rect: Vec<ComponentWithRect>,
     should_quit: bool,
     should_suspend: bool,
+    /// Whether the screen is out of date; a render is requested from the `Tui` while it is.
+    dirty: bool,
+    rendered_revision: u64,
     mode: Mode,
+    /// The tab shown underneath any popup.
+    tab: Mode,
+    /// The tab to go back to from the logs tab, see [`Action::ToggleLogViewer`].
+    previous_tab: Mode,
+    /// Per tab, the position of the focused component among its focusable ones.
+    focus: HashMap<Mode, usize>,
+    /// The open popups, the topmost last. It takes all input and is closed first by `<Esc>`.
+    popups: Vec<Mode>,
+    /// The action of the open confirmation dialog, see [`Action::Confirm`].
+    pending_confirmation: Option<Action>,
+    state: AppState,
+    metrics: Metrics,
+    tasks: TaskManager,
+    /// The task of the last [`Action::CountTo`], cancelled by the next one.
+    count_task: Option<u64>,
     last_tick_key_events: Vec<KeyEvent>,
-    action_tx: mpsc::UnboundedSender<Action>,
-    action_rx: mpsc::UnboundedReceiver<Action>,
+    action_tx: ActionSender,
+    action_rx: ActionReceiver,
 }
 
 #[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum Mode {
     #[default]
     Home,
+    /// The counter on its own.
+    Counter,
+    /// Recent log messages.
+    Logs,
+    /// The settings and keybindings in effect.
+    Settings,
+    /// The command palette is open and receives all key events.
+    CommandPalette,
+    /// The keybindings of the current tab are shown and receive all key events.
+    Help,
+    /// A confirmation dialog is open and receives all key events, see [`Action::Confirm`].
+    Confirm,
+}
+
+impl Mode {
+    /// The modes shown as tabs, in order.
+    pub const TABS: [Mode; 4] = [Mode::Home, Mode::Counter, Mode::Logs, Mode::Settings];
+
+    /// Whether the mode is a popup over the other modes, which receives all key events. Opening
+    /// or closing one does not count as a mode switch for background tasks.
+    pub fn is_overlay(self) -> bool {
+        matches!(self, Mode::CommandPalette | Mode::Help | Mode::Confirm)
+    }
+
+    /// Whether the built-in counter keys of [`EventHandler`] apply, e.g. the arrow keys.
+    pub fn has_counter_keys(self) -> bool {
+        matches!(self, Mode::Home | Mode::Counter)
+    }
+
+    /// The title of the tab in the tab bar.
+    pub fn title(self) -> &'static str {
+        match self {
+            Mode::Home => "Home",
+            Mode::Counter => "Counter",
+            Mode::Logs => "Logs",
+            Mode::Settings => "Settings",
+            Mode::CommandPalette => "Command Palette",
+            Mode::Help => "Help",
+            Mode::Confirm => "Confirm",
+        }
+    }
+
+    /// The tab `offset` tabs after this one, wrapping around.
+    pub fn cycle_tab(self, offset: isize) -> Mode {
+        let index = Mode::TABS
+            .iter()
+            .position(|tab| *tab == self)
+            .unwrap_or_default();
+        let index = (index as isize + offset).rem_euclid(Mode::TABS.len() as isize);
+        Mode::TABS[index as usize]
+    }
 }
 
 impl App {
-    fn generate_components_layout() -> Result<Vec<ComponentWithRect>> {
-        let tui = Tui::new()?;
-        let size = tui.size()?;
-        let chunks = Layout::default()
-            .direction(Direction::Vertical)
-            .constraints(
-                [
-                    Constraint::Percentage(10),
-                    Constraint::Percentage(40),
-                    Constraint::Percentage(50),
-                ]
-                .as_ref(),
-            )
-            .split(Rect {
-                x: 0,
-                y: 0,
-                width: size.width,
-                height: size.height,
-            });
-
-        Ok(vec![
-            ComponentWithRect {
-                component: Box::new(Home::new()),
-                rect: chunks[0],
-            },
-            ComponentWithRect {
-                component: Box::new(FpsCounter::default()),
-                rect: chunks[1],
-            },
-            ComponentWithRect {
-                component: Box::new(Counter::default()),
-                rect: chunks[2],
-            },
+    fn generate_components_layout() -> Vec<ComponentWithRect> {
+        let component = |component: Box<dyn Component>, slot, tab| ComponentWithRect {
+            component,
+            slot,
+            tab,
+            rect: Rect::default(),
+        };
+        vec![
+            component(Box::new(TabBar::new()), Slot::TabBar, None),
+            component(Box::new(Home::new()), Slot::Header, Some(Mode::Home)),
+            component(Box::<FpsCounter>::default(), Slot::Stats, Some(Mode::Home)),
+            component(Box::<Counter>::default(), Slot::Main, Some(Mode::Home)),
+            component(Box::<Counter>::default(), Slot::Full, Some(Mode::Counter)),
+            component(Box::<LogViewer>::default(), Slot::Full, Some(Mode::Logs)),
+            component(Box::new(Settings::new()), Slot::Left, Some(Mode::Settings)),
+            component(
+                Box::new(Keybindings::new()),
+                Slot::Right,
+                Some(Mode::Settings),
+            ),
+            component(Box::new(StatusBar::new()), Slot::StatusBar, None),
+            // Drawn last so that they overlay the other components while open.
+            component(Box::new(Help::new()), Slot::Popup(Mode::Help), None),
+            component(
+                Box::new(CommandPalette::new()),
+                Slot::Popup(Mode::CommandPalette),
+                None,
+            ),
+            component(
+                Box::new(ConfirmDialog::new()),
+                Slot::Popup(Mode::Confirm),
+                None,
+            ),
+        ]
+    }
+
+    /// Places the components shown in `tab` and the open `popups` within `area`, the full screen
+    /// or the inline viewport. The others get an empty area.
+    fn update_layout(components: &mut [ComponentWithRect], area: Rect, tab: Mode, popups: &[Mode]) {
+        let [tab_bar, content, status_bar] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Fill(1),
+            Constraint::Length(1),
         ])
+        .areas(area);
+        let [header, stats, main] = Layout::vertical([
+            Constraint::Percentage(10),
+            Constraint::Percentage(40),
+            Constraint::Percentage(50),
+        ])
+        .areas(content);
+        let [left, right] =
+            Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)])
+                .areas(content);
+        for component_info in components {
+            if let Slot::Popup(mode) = component_info.slot {
+                component_info.rect = if popups.contains(&mode) {
+                    let (width, height) = component_info.component.popup_size();
+                    let [popup] = Layout::horizontal([width])
+                        .flex(Flex::Center)
+                        .areas(content);
+                    let [popup] = Layout::vertical([height]).flex(Flex::Center).areas(popup);
+                    popup
+                } else {
+                    Rect::default()
+                };
+                continue;
+            }
+            if !component_info.is_shown(tab) {
+                component_info.rect = Rect::default();
+                continue;
+            }
+            component_info.rect = match component_info.slot {
+                Slot::TabBar => tab_bar,
+                Slot::Header => header,
+                Slot::Stats => stats,
+                Slot::Main => main,
+                Slot::Full => content,
+                Slot::Left => left,
+                Slot::Right => right,
+                Slot::StatusBar => status_bar,
+                Slot::Popup(_) => unreachable!("placed above"),
+            };
+        }
     }
+
     pub fn new(tick_rate: f64, frame_rate: f64) -> Result<Self> {
-        let (action_tx, action_rx) = mpsc::unbounded_channel();
+        Self::with_config(tick_rate, frame_rate, Config::new()?)
+    }
 
+    fn with_config(tick_rate: f64, frame_rate: f64, config: Config) -> Result<Self> {
+        let (action_tx, action_rx) = action_queue::channel(DEFAULT_QUEUE_CAPACITY);
         Ok(Self {
-            tick_rate,
-            frame_rate,
-            components_with_rect: Self::generate_components_layout()?,
+            tick_rate: validate_rate(tick_rate)?,
+            frame_rate: validate_rate(frame_rate)?,
+            keyboard_enhancement: false,
+            inline: None,
+            queue_capacity: DEFAULT_QUEUE_CAPACITY,
+            commands: None,
+            command_input: None,
+            control_socket: None,
+            control_rx: None,
+            command_rx: None,
+            pending_control_replies: Vec::new(),
+            components_with_rect: Self::generate_components_layout(),
             should_quit: false,
             should_suspend: false,
-            config: Config::new()?,
+            dirty: true,
+            rendered_revision: 0,
+            state: AppState::new().with_counter(config.config.counter),
+            config,
             mode: Mode::Home,
+            tab: Mode::Home,
+            previous_tab: Mode::Home,
+            focus: HashMap::new(),
+            popups: Vec::new(),
+            pending_confirmation: None,
+            metrics: Metrics::new(),
+            tasks: TaskManager::new(action_tx.clone()),
+            count_task: None,
             last_tick_key_events: Vec::new(),
             action_tx,
             action_rx,
         })
     }
 
-    pub async fn run(&mut self) -> Result<()> {
-        let mut tui = Tui::new()?
-            .tick_rate(self.tick_rate)
-            .frame_rate(self.frame_rate);
-        tui.enter()?;
+    pub fn keyboard_enhancement(mut self, keyboard_enhancement: bool) -> Self {
+        self.keyboard_enhancement = keyboard_enhancement;
+        self
+    }
 
-        // Create shared state once and share it with components
-        let state = AppState::new();
+    /// Run in an inline viewport of this height instead of the alternate screen. Overrides
+    /// `inline_height` from the config.
+    pub fn inline(mut self, height: Option<u16>) -> Self {
+        self.inline = height;
+        self
+    }
 
-        // Create centralized event handler
-        let event_handler = EventHandler::new(state.clone());
+    /// Capacity of the action, event and control request queues.
+    pub fn queue_capacity(mut self, capacity: NonZeroUsize) -> Self {
+        self.queue_capacity = capacity;
+        (self.action_tx, self.action_rx) = action_queue::channel(capacity);
+        self.tasks = TaskManager::new(self.action_tx.clone());
+        self
+    }
+
+    /// Actions from this file are queued before the first event is handled.
+    pub fn commands(mut self, commands: Option<PathBuf>) -> Self {
+        self.commands = commands;
+        self
+    }
+
+    /// Actions are read from this file (or stdin for `-`) for as long as the app runs.
+    pub fn command_input(mut self, command_input: Option<PathBuf>) -> Self {
+        self.command_input = command_input;
+        self
+    }
+
+    /// Serve the control protocol on a Unix domain socket at this path while the app runs.
+    pub fn control_socket(mut self, control_socket: Option<PathBuf>) -> Self {
+        self.control_socket = control_socket;
+        self
+    }
 
+    /// Hands the action queue, config, state, metrics and task handle to every component.
+    fn register_components(&mut self) -> Result<()> {
         for component_info in self.components_with_rect.iter_mut() {
             component_info
                 .component
@@ -120,123 +364,969 @@ impl App {
         for component_info in self.components_with_rect.iter_mut() {
             component_info
                 .component
-                .register_state_handler(state.clone())?;
+                .register_state_handler(self.state.clone())?;
+        }
+        for component_info in self.components_with_rect.iter_mut() {
+            component_info
+                .component
+                .register_metrics_handler(self.metrics.clone())?;
+        }
+        for component_info in self.components_with_rect.iter_mut() {
+            component_info
+                .component
+                .register_task_handler(self.tasks.handle())?;
+        }
+        Ok(())
+    }
+
+    pub async fn run(&mut self) -> Result<()> {
+        // Read the startup commands before touching the terminal so that errors are readable.
+        let startup_actions = match &self.commands {
+            Some(path) => read_command_file(path)?,
+            None => Vec::new(),
+        };
+        if let Some(path) = &self.command_input {
+            check_command_input(path)?;
         }
+
+        let mut tui = Tui::new()?
+            .tick_rate(self.tick_rate)
+            .frame_rate(self.frame_rate)
+            .focus(true)
+            .mouse(true)
+            .event_capacity(self.queue_capacity)
+            .inline(self.inline.or(self.config.config.inline_height))
+            .keyboard_enhancement(self.keyboard_enhancement);
+        tui.enter()?;
+
+        // Share the state with components
+        let state = self.state.clone();
+        crash::set_context(state.clone(), &self.config);
+
+        // Create centralized event handler
+        let mut event_handler = EventHandler::new(state.clone());
+
+        self.register_components()?;
         for component_info in self.components_with_rect.iter_mut() {
             component_info.component.init(tui.size()?)?;
         }
+        self.set_focus(true);
 
         let action_tx = self.action_tx.clone();
+        for action in startup_actions {
+            self.dispatch(action)?;
+        }
+        let command_reader = self.command_input.clone().map(|path| {
+            let (command_tx, command_rx) = mpsc::channel(self.queue_capacity.get());
+            self.command_rx = Some(command_rx);
+            spawn_command_reader(path, command_tx)
+        });
+        let signal_handler = spawn_signal_handler(action_tx.clone())?;
+        let control_server = match self.control_socket.clone() {
+            Some(path) => {
+                let (control_tx, control_rx) = mpsc::channel(self.queue_capacity.get());
+                self.control_rx = Some(control_rx);
+                Some(spawn_control_server(path, state.clone(), control_tx)?)
+            }
+            None => None,
+        };
+
         loop {
-            self.handle_events(&mut tui, &event_handler).await?;
+            self.handle_events(&mut tui, &mut event_handler).await?;
             self.handle_actions(&mut tui)?;
+            for reply in self.pending_control_replies.drain(..) {
+                let _ = reply.send(());
+            }
             if self.should_suspend {
-                tui.suspend()?;
-                action_tx.send(Action::Resume)?;
-                action_tx.send(Action::ClearScreen)?;
-                tui.enter()?;
+                self.suspend(&mut tui)?;
             } else if self.should_quit {
-                tui.stop()?;
                 break;
             }
         }
+        let input_tasks = command_reader.into_iter().chain(control_server).collect();
+        self.shutdown(&mut tui, input_tasks).await?;
+        if tui.inline_height.is_some() {
+            // The last frame stays in the scrollback, so make sure it is up to date.
+            self.render(&mut tui)?;
+        }
         tui.exit()?;
+        // Kept until the terminal is restored, so that a second signal can still force it.
+        signal_handler.close();
         Ok(())
     }
 
-    async fn handle_events(&mut self, tui: &mut Tui, event_handler: &EventHandler) -> Result<()> {
-        let Some(event) = tui.next_event().await else {
+    /// Shuts down in order: input stops, background tasks get [`SHUTDOWN_TIMEOUT`] to finish, the
+    /// actions they sent are handled, and finally components flush their state.
+    async fn shutdown(&mut self, tui: &mut Tui, input_tasks: Vec<JoinHandle<()>>) -> Result<()> {
+        tui.stop().await?;
+        for task in input_tasks {
+            task.abort();
+        }
+        if let Some(path) = &self.control_socket {
+            remove_socket(path);
+        }
+        // Clients still waiting for a reply are told that the app is shutting down.
+        self.control_rx = None;
+
+        self.tasks.shutdown(SHUTDOWN_TIMEOUT).await?;
+        self.handle_actions(tui)?;
+        for reply in self.pending_control_replies.drain(..) {
+            let _ = reply.send(());
+        }
+
+        for component_info in self.components_with_rect.iter_mut() {
+            let component = &mut component_info.component;
+            if let Err(err) = component.shutdown() {
+                error!("{} failed to shut down: {err:?}", component.name());
+            }
+        }
+        Ok(())
+    }
+
+    #[instrument(level = "debug", skip_all)]
+    async fn handle_events(
+        &mut self,
+        tui: &mut Tui,
+        event_handler: &mut EventHandler,
+    ) -> Result<()> {
+        let event = tokio::select! {
+            event = tui.next_event() => event,
+            Some(request) = next_request(&mut self.control_rx) => {
+                self.dispatch(request.action)?;
+                self.pending_control_replies.push(request.processed);
+                return Ok(());
+            }
+            Some(action) = next_request(&mut self.command_rx) => {
+                return self.dispatch(action);
+            }
+        };
+        let Some(event) = event else {
             return Ok(());
         };
 
         let action_tx = self.action_tx.clone();
+        match event {
+            Event::Quit => action_tx.send(Action::Quit)?,
+            Event::Tick => action_tx.send(Action::Tick)?,
+            Event::Render if self.dirty => self.render(tui)?,
+            Event::Resize(x, y) => action_tx.send(Action::Resize(x, y))?,
+            Event::FocusLost => tui.throttle(true),
+            Event::FocusGained => tui.throttle(false),
+            Event::Key(key) => self.handle_key(key, event_handler)?,
+            Event::Mouse(mouse) => self.handle_mouse_event(mouse)?,
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Gives a key to the topmost popup if one is open, or else to the focused component if it
+    /// captures keys, the [`EventHandler`] in the counter tabs, and finally the keybindings.
+    fn handle_key(&mut self, key: KeyEvent, event_handler: &mut EventHandler) -> Result<()> {
+        let action_tx = self.action_tx.clone();
+        if let Some(index) = self.top_popup() {
+            self.dirty = true;
+            if key.code == KeyCode::Esc {
+                if key.kind == KeyEventKind::Press {
+                    action_tx.send(Action::ClosePopup)?;
+                }
+            } else if let Some(action) = self.components_with_rect[index]
+                .component
+                .handle_key_event(key)?
+            {
+                self.dispatch(action)?;
+            }
+            return Ok(());
+        }
+        if self
+            .focused()
+            .is_some_and(|index| self.components_with_rect[index].component.captures_keys())
+        {
+            return self.send_to_focused(key);
+        }
 
         // Handle event with the centralized event handler
-        if let Some(action) = event_handler.handle(event.clone())? {
-            action_tx.send(action)?;
+        let handled = if self.mode.has_counter_keys() {
+            event_handler.handle(Event::Key(key))?
         } else {
-            // Only send standard events if not already handled by event handler
-            match event {
-                Event::Quit => action_tx.send(Action::Quit)?,
-                Event::Tick => action_tx.send(Action::Tick)?,
-                Event::Render => action_tx.send(Action::Render)?,
-                Event::Resize(x, y) => action_tx.send(Action::Resize(x, y))?,
-                Event::Key(key) => self.handle_key_event(key)?,
-                _ => {}
-            }
+            Handled::Ignored
+        };
+        match handled {
+            Handled::Action(action) => self.dispatch(action)?,
+            Handled::Consumed => {}
+            Handled::Ignored => self.handle_key_event(key)?,
         }
         Ok(())
     }
 
     fn handle_key_event(&mut self, key: KeyEvent) -> Result<()> {
-        let action_tx = self.action_tx.clone();
         let Some(keymap) = self.config.keybindings.get(&self.mode) else {
             return Ok(());
         };
-        match keymap.get(&vec![key]) {
+        // Terminals without the kitty keyboard protocol report held keys as repeated presses, so
+        // a repeat without a dedicated binding falls back to the press binding.
+        let action = keymap.get(&vec![key]).or_else(|| match key.kind {
+            KeyEventKind::Repeat => keymap.get(&vec![KeyEvent {
+                kind: KeyEventKind::Press,
+                ..key
+            }]),
+            _ => None,
+        });
+        // Whether the key is left to the focused component.
+        let unbound = match action {
             Some(action) => {
                 info!("Got action: {action:?}");
-                action_tx.send(action.clone())?;
+                self.dispatch(action.clone())?;
+                false
             }
+            // Only presses take part in multi-key combinations.
+            None if key.kind != KeyEventKind::Press => true,
             _ => {
                 // If the key was not handled as a single key action,
                 // then consider it for multi-key combinations.
                 self.last_tick_key_events.push(key);
 
                 // Check for multi-key combinations
-                if let Some(action) = keymap.get(&self.last_tick_key_events) {
+                let pending = &self.last_tick_key_events;
+                if let Some(action) = keymap.get(pending) {
                     info!("Got action: {action:?}");
-                    action_tx.send(action.clone())?;
+                    self.dispatch(action.clone())?;
+                    self.state.set_pending_keys(String::new())?;
+                    false
+                } else if keymap
+                    .keys()
+                    .any(|keys| keys.len() > pending.len() && keys.starts_with(pending))
+                {
+                    self.state
+                        .set_pending_keys(key_sequence_to_string(pending))?;
+                    false
+                } else {
+                    self.state.set_pending_keys(String::new())?;
+                    true
                 }
             }
+        };
+        if unbound {
+            self.send_to_focused(key)?;
+        }
+        Ok(())
+    }
+
+    /// Gives a key event to the component with the focus in the current tab, if any.
+    fn send_to_focused(&mut self, key: KeyEvent) -> Result<()> {
+        let Some(index) = self.focused() else {
+            return Ok(());
+        };
+        self.dirty = true;
+        if let Some(action) = self.components_with_rect[index]
+            .component
+            .handle_key_event(key)?
+        {
+            self.dispatch(action)?;
         }
         Ok(())
     }
 
+    /// Gives a mouse event to the components under the pointer in the current tab. A click also
+    /// focuses the component. While a popup is open, only the topmost one gets mouse events.
+    fn handle_mouse_event(&mut self, mouse: MouseEvent) -> Result<()> {
+        if mouse.kind == MouseEventKind::Moved {
+            return Ok(());
+        }
+        self.dirty = true;
+        let position = Position::new(mouse.column, mouse.row);
+        if let Some(index) = self.top_popup() {
+            let component_info = &mut self.components_with_rect[index];
+            let rect = component_info.rect;
+            if !rect.contains(position) {
+                return Ok(());
+            }
+            return match component_info.component.handle_mouse_event(mouse, rect)? {
+                Some(action) => self.dispatch(action),
+                None => Ok(()),
+            };
+        }
+        let under_pointer = |component_info: &ComponentWithRect| {
+            component_info.is_shown(self.tab) && component_info.rect.contains(position)
+        };
+        if mouse.kind == MouseEventKind::Down(MouseButton::Left) {
+            let clicked = self
+                .focusable(self.tab)
+                .iter()
+                .position(|index| under_pointer(&self.components_with_rect[*index]));
+            if let Some(position) = clicked {
+                self.set_focus(false);
+                self.focus.insert(self.tab, position);
+                self.set_focus(true);
+            }
+        }
+        let tab = self.tab;
+        let mut actions = Vec::new();
+        for component_info in self.components_with_rect.iter_mut() {
+            if !component_info.is_shown(tab) || !component_info.rect.contains(position) {
+                continue;
+            }
+            let rect = component_info.rect;
+            actions.extend(component_info.component.handle_mouse_event(mouse, rect)?);
+        }
+        actions
+            .into_iter()
+            .try_for_each(|action| self.dispatch(action))
+    }
+
+    /// Indices of the focusable components shown in `tab`, in order.
+    fn focusable(&self, tab: Mode) -> Vec<usize> {
+        self.components_with_rect
+            .iter()
+            .enumerate()
+            .filter(|(_, component_info)| {
+                component_info.is_shown(tab) && component_info.component.focusable()
+            })
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    /// Index of the component with the focus in the current tab, if any.
+    fn focused(&self) -> Option<usize> {
+        let position = self.focus.get(&self.tab).copied().unwrap_or_default();
+        self.focusable(self.tab).get(position).copied()
+    }
+
+    /// Tells the focused component of the current tab that it gained or lost the focus.
+    fn set_focus(&mut self, focused: bool) {
+        if let Some(index) = self.focused() {
+            self.components_with_rect[index]
+                .component
+                .set_focused(focused);
+        }
+    }
+
+    /// Moves the focus `offset` components on within the current tab, wrapping around.
+    fn move_focus(&mut self, offset: isize) {
+        let len = self.focusable(self.tab).len();
+        if len == 0 {
+            return;
+        }
+        self.set_focus(false);
+        let position = self.focus.entry(self.tab).or_default();
+        *position = (*position as isize + offset).rem_euclid(len as isize) as usize;
+        self.set_focus(true);
+    }
+
+    /// Shows `tab` underneath any open popup, and gives the focus back to the component that had
+    /// it when the tab was last shown.
+    fn select_tab(&mut self, tab: Mode) -> Result<()> {
+        if tab == self.tab {
+            return Ok(());
+        }
+        self.set_focus(false);
+        self.previous_tab = self.tab;
+        self.tab = tab;
+        self.set_focus(true);
+        if self.popups.is_empty() {
+            self.set_mode(tab)
+        } else {
+            self.tasks.switch_mode()
+        }
+    }
+
+    /// Index of the component of the topmost open popup, if any.
+    fn top_popup(&self) -> Option<usize> {
+        let mode = *self.popups.last()?;
+        self.components_with_rect
+            .iter()
+            .position(|component_info| component_info.slot == Slot::Popup(mode))
+    }
+
+    /// Puts the popup for `mode` on top of the others, moving it there if it is already open.
+    fn open_popup(&mut self, mode: Mode) -> Result<()> {
+        self.popups.retain(|open| *open != mode);
+        self.popups.push(mode);
+        self.set_mode(mode)
+    }
+
+    /// Closes the popup for `mode`, or the topmost one for `None`, and returns to the popup
+    /// below it or the current tab.
+    fn close_popup(&mut self, mode: Option<Mode>) -> Result<()> {
+        match mode {
+            Some(mode) => self.popups.retain(|open| *open != mode),
+            None => {
+                self.popups.pop();
+            }
+        }
+        // However the confirmation dialog is closed, its action is not run any more.
+        if !self.popups.contains(&Mode::Confirm) {
+            self.pending_confirmation = None;
+        }
+        self.set_mode(self.popups.last().copied().unwrap_or(self.tab))
+    }
+
+    #[instrument(level = "debug", skip_all)]
     fn handle_actions(&mut self, tui: &mut Tui) -> Result<()> {
-        while let Ok(action) = self.action_rx.try_recv() {
+        self.drain_actions(|app, action| app.handle_terminal_action(tui, action))?;
+        if self.dirty {
+            tui.request_render();
+        }
+        Ok(())
+    }
+
+    /// Handles the actions that act on the terminal.
+    fn handle_terminal_action(&mut self, tui: &mut Tui, action: &Action) -> Result<()> {
+        match *action {
+            Action::Resume => self.resume(tui)?,
+            Action::ClearScreen => tui.terminal.clear()?,
+            Action::Resize(..) => self.handle_resize(tui)?,
+            Action::SetTickRate(rate) => match tui.set_tick_rate(rate) {
+                Ok(()) => self.tick_rate = rate,
+                Err(err) => self.action_tx.send(Action::Error(err.to_string()))?,
+            },
+            Action::SetFrameRate(rate) => match tui.set_frame_rate(rate) {
+                Ok(()) => self.frame_rate = rate,
+                Err(err) => self.action_tx.send(Action::Error(err.to_string()))?,
+            },
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Handles the queued actions, and the ones they lead to, until the queue is empty. Each
+    /// action is passed to `terminal` as well, for the actions that act on the terminal.
+    fn drain_actions(
+        &mut self,
+        mut terminal: impl FnMut(&mut Self, &Action) -> Result<()>,
+    ) -> Result<()> {
+        self.metrics.record_queue_depth(self.action_rx.len());
+        // Actions emitted by components during this drain, to break feedback loops.
+        let mut emitted = HashMap::<String, usize>::new();
+        while let Some(action) = self.action_rx.try_recv() {
+            let action = self.check_confirmation(action)?;
             if action != Action::Tick && action != Action::Render {
                 debug!("{action:?}");
+                crash::record_action(&action);
+            }
+            // Anything but a tick may change what is on screen; `Render` only requests a redraw.
+            if action != Action::Tick {
+                self.dirty = true;
             }
+            terminal(self, &action)?;
             match action {
                 Action::Tick => {
                     self.last_tick_key_events.drain(..);
+                    self.state.set_pending_keys(String::new())?;
                 }
-                Action::Quit => self.should_quit = true,
+                Action::Quit if self.quit_prompt()?.is_some() => {
+                    self.action_tx.send(self.confirmation(Action::Quit)?)?
+                }
+                Action::Quit | Action::ForceQuit => self.should_quit = true,
+                Action::Confirm(ref action, _) => {
+                    self.pending_confirmation = Some(*action.clone());
+                    self.open_popup(Mode::Confirm)?
+                }
+                Action::CancelConfirm => self.close_popup(Some(Mode::Confirm))?,
                 Action::Suspend => self.should_suspend = true,
-                Action::Resume => self.should_suspend = false,
-                Action::ClearScreen => tui.terminal.clear()?,
-                Action::Resize(w, h) => self.handle_resize(tui, w, h)?,
-                Action::Render => self.render(tui)?,
+                Action::OpenCommandPalette => self.open_popup(Mode::CommandPalette)?,
+                Action::CloseCommandPalette => self.close_popup(Some(Mode::CommandPalette))?,
+                Action::Help => self.open_popup(Mode::Help)?,
+                Action::ClosePopup => self.close_popup(None)?,
+                Action::ToggleLogViewer => match self.tab {
+                    Mode::Logs => self.select_tab(self.previous_tab)?,
+                    _ => self.select_tab(Mode::Logs)?,
+                },
+                Action::NextTab => self.select_tab(self.tab.cycle_tab(1))?,
+                Action::PrevTab => self.select_tab(self.tab.cycle_tab(-1))?,
+                Action::SelectTab(tab) if Mode::TABS.contains(&tab) => self.select_tab(tab)?,
+                Action::SelectTab(mode) => self
+                    .action_tx
+                    .send(Action::Error(format!("{mode:?} is not a tab")))?,
+                Action::CountTo(target) => self.count_to(target)?,
+                Action::FocusNext => self.move_focus(1),
+                Action::FocusPrev => self.move_focus(-1),
+                Action::SetLogLevel(ref directive) => match logging::set_level(directive) {
+                    Ok(()) => info!("Log level set to {directive}"),
+                    Err(err) => self.action_tx.send(Action::Error(format!(
+                        "Invalid log level {directive}: {err}"
+                    )))?,
+                },
                 _ => {}
             }
+            self.state.apply(&action)?;
             for component_info in self.components_with_rect.iter_mut() {
-                if let Some(action) = component_info.component.update(action.clone())? {
-                    self.action_tx.send(action)?
+                let component = &mut component_info.component;
+                let span = trace_span!("update", component = component.name());
+                let Some(action) = span.in_scope(|| component.update(action.clone()))? else {
+                    continue;
                 };
+                let repeats = emitted.entry(action.to_command()).or_default();
+                *repeats += 1;
+                if *repeats > MAX_REPEATS_PER_DRAIN {
+                    if *repeats == MAX_REPEATS_PER_DRAIN + 1 {
+                        warn!(
+                            "Action cycle detected: {} emitted {action:?} more than \
+                             {MAX_REPEATS_PER_DRAIN} times, dropping it until the next drain",
+                            component.name()
+                        );
+                    }
+                    continue;
+                }
+                self.action_tx.send(action)?
+            }
+        }
+        if self.state.revision() != self.rendered_revision {
+            self.dirty = true;
+        }
+        Ok(())
+    }
+
+    /// Spawns the task of [`Action::CountTo`], which moves the count by at most the amount every
+    /// [`COUNT_STEP_INTERVAL`] and reports how far along it is. It stops when another tab is
+    /// selected, but keeps going under popups.
+    fn count_to(&mut self, target: i32) -> Result<()> {
+        let handle = self.tasks.handle();
+        if let Some(id) = self.count_task.take() {
+            handle.cancel(id)?;
+        }
+        let target = self.config.config.counter.clamp(target);
+        let distance = i64::from(target) - i64::from(self.state.get_count()?);
+        let amount = self.state.get_amount()?.parse::<i64>().unwrap_or(1).max(1);
+        let id = handle.spawn(
+            &format!("Counting to {target}"),
+            move |context| async move {
+                let mut interval = tokio::time::interval(COUNT_STEP_INTERVAL);
+                let mut moved = 0;
+                while moved < distance.abs() {
+                    interval.tick().await;
+                    // At most the amount, which is an i32.
+                    let step = amount.min(distance.abs() - moved);
+                    moved += step;
+                    let action = if distance > 0 {
+                        Action::IncrementBy(step as i32)
+                    } else {
+                        Action::DecrementBy(step as i32)
+                    };
+                    context.send(action).await?;
+                    context
+                        .progress(moved as f64 / distance.abs() as f64)
+                        .await?;
+                }
+                Ok(None)
+            },
+        )?;
+        self.count_task = Some(id);
+        Ok(())
+    }
+
+    /// Sends an action the user asked for, with a key, a click, the command palette, the command
+    /// input or the control socket, asking first if it needs to be confirmed. Actions from
+    /// anywhere else, e.g. signals and background tasks, are sent as they are.
+    fn dispatch(&self, action: Action) -> Result<()> {
+        self.action_tx.send(self.confirmation(action)?)
+    }
+
+    /// Wraps an action that needs to be confirmed in [`Action::Confirm`]: quitting while background
+    /// tasks are running if `confirm_quit` is enabled, which asks once, naming the tasks, and the
+    /// actions configured in `confirm`.
+    fn confirmation(&self, action: Action) -> Result<Action> {
+        if action == Action::Quit {
+            if let Some(prompt) = self.quit_prompt()? {
+                return Ok(Action::Confirm(Box::new(Action::ForceQuit), prompt));
+            }
+        }
+        Ok(match self.config.config.confirm.prompt(&action) {
+            Some(prompt) => Action::Confirm(Box::new(action), prompt.clone()),
+            None => action,
+        })
+    }
+
+    /// The prompt for quitting if it needs to be confirmed, i.e. `confirm_quit` is enabled and
+    /// background tasks are running, which lists the tasks that would be cancelled.
+    fn quit_prompt(&self) -> Result<Option<String>> {
+        if !self.config.config.confirm_quit {
+            return Ok(None);
+        }
+        let running = self.tasks.handle().running()?;
+        if running.is_empty() {
+            return Ok(None);
+        }
+        let mut lines = running
+            .into_iter()
+            .map(|(_, name)| format!("{name} will be cancelled."))
+            .collect::<Vec<_>>();
+        lines.push("Quit anyway?".to_string());
+        Ok(Some(lines.join("\n")))
+    }
+
+    /// Only one action waits for confirmation at a time, so another [`Action::Confirm`] is
+    /// rejected until the dialog is answered. [`Action::Confirmed`] is only accepted for the
+    /// action that is waiting, which it unwraps, closing the dialog; anything else could skip the
+    /// confirmation.
+    fn check_confirmation(&mut self, action: Action) -> Result<Action> {
+        Ok(match action {
+            Action::Confirm(action, _) if self.pending_confirmation.is_some() => Action::Error(
+                format!("Not running {action:?}, already waiting for a confirmation"),
+            ),
+            Action::Confirmed(action) if self.pending_confirmation.as_ref() == Some(&*action) => {
+                self.close_popup(Some(Mode::Confirm))?;
+                *action
+            }
+            Action::Confirmed(action) => {
+                warn!("Dropping confirmation of {action:?}, which is not waiting for one");
+                Action::Error(format!("{action:?} is not waiting for a confirmation"))
             }
+            action => action,
+        })
+    }
+
+    fn set_mode(&mut self, mode: Mode) -> Result<()> {
+        if mode != self.mode && !mode.is_overlay() && !self.mode.is_overlay() {
+            self.tasks.switch_mode()?;
+        }
+        self.mode = mode;
+        self.state.set_mode(mode)
+    }
+
+    /// Stops the process until it is continued, see [`Tui::suspend`].
+    fn suspend(&mut self, tui: &mut Tui) -> Result<()> {
+        self.should_suspend = false;
+        for component_info in self.components_with_rect.iter_mut() {
+            component_info.component.on_suspend()?;
         }
+        // Returns once the process is continued.
+        tui.suspend()?;
+        self.resume(tui)
+    }
+
+    /// Resumes after [`App::suspend`], and handles [`Action::Resume`], which is sent when the
+    /// process is continued after it was stopped from outside.
+    fn resume(&mut self, tui: &mut Tui) -> Result<()> {
+        tui.resume()?;
+        for component_info in self.components_with_rect.iter_mut() {
+            component_info.component.on_resume()?;
+        }
+        // The terminal may have been resized or drawn over in the meantime.
+        let size = tui.size()?;
+        self.action_tx
+            .send(Action::Resize(size.width, size.height))?;
+        self.action_tx.send(Action::ClearScreen)?;
         Ok(())
     }
 
-    fn handle_resize(&mut self, tui: &mut Tui, w: u16, h: u16) -> Result<()> {
-        tui.resize(Rect::new(0, 0, w, h))?;
+    fn handle_resize(&mut self, tui: &mut Tui) -> Result<()> {
+        // Resizes the inline viewport correctly as well, unlike `resize` with the full size.
+        tui.autoresize()?;
         self.render(tui)?;
         Ok(())
     }
 
+    #[instrument(level = "debug", skip_all)]
     fn render(&mut self, tui: &mut Tui) -> Result<()> {
+        self.dirty = false;
+        self.rendered_revision = self.state.revision();
+        let frame_start = Instant::now();
+        let mut draw_times = Vec::with_capacity(self.components_with_rect.len());
+        // The components of the current tab in order, then the open popups from the bottom up.
+        let mut draw_order = (0..self.components_with_rect.len())
+            .filter(|index| self.components_with_rect[*index].is_shown(self.tab))
+            .collect::<Vec<_>>();
+        draw_order.extend(self.popups.iter().filter_map(|mode| {
+            self.components_with_rect
+                .iter()
+                .position(|component_info| component_info.slot == Slot::Popup(*mode))
+        }));
         tui.draw(|frame| {
-            for component_info in self.components_with_rect.iter_mut() {
+            Self::update_layout(
+                &mut self.components_with_rect,
+                frame.area(),
+                self.tab,
+                &self.popups,
+            );
+            for index in draw_order {
+                let component_info = &mut self.components_with_rect[index];
+                if let Slot::Popup(_) = component_info.slot {
+                    frame.render_widget(Clear, component_info.rect);
+                }
                 let component = &mut component_info.component;
                 let rect = component_info.rect;
-                if let Err(err) = component.draw(frame, rect) {
+                let draw_start = Instant::now();
+                let span = trace_span!("draw", component = component.name());
+                if let Err(err) = span.in_scope(|| component.draw(frame, rect)) {
                     let _ = self
                         .action_tx
                         .send(Action::Error(format!("Failed to draw: {:?}", err)));
                 }
+                draw_times.push((component.name().to_string(), draw_start.elapsed()));
             }
         })?;
+        self.metrics.record_frame(frame_start.elapsed(), draw_times);
         Ok(())
     }
 }
+
+/// Waits for the next control request or command input, or forever if it is disabled.
+async fn next_request<T>(rx: &mut Option<mpsc::Receiver<T>>) -> Option<T> {
+    match rx {
+        Some(rx) => rx.recv().await,
+        None => std::future::pending().await,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    /// An app with `config` and its components set up, but no terminal.
+    fn app(config: Config) -> App {
+        let mut app = App::with_config(4.0, 60.0, config).unwrap();
+        app.register_components().unwrap();
+        app
+    }
+
+    /// Handles the queued actions, leaving out what acts on the terminal.
+    fn handle(app: &mut App) {
+        app.drain_actions(|_, _| Ok(())).unwrap();
+    }
+
+    fn send(app: &mut App, action: Action) {
+        app.action_tx.send(action).unwrap();
+        handle(app);
+    }
+
+    /// Sends `action` like a key would, and handles it.
+    fn dispatch(app: &mut App, action: Action) {
+        app.dispatch(action).unwrap();
+        handle(app);
+    }
+
+    fn press(app: &mut App, code: KeyCode) {
+        let mut event_handler = EventHandler::new(app.state.clone());
+        let key = KeyEvent::new(code, crossterm::event::KeyModifiers::empty());
+        app.handle_key(key, &mut event_handler).unwrap();
+        handle(app);
+    }
+
+    fn click(app: &mut App, column: u16, row: u16) {
+        App::update_layout(
+            &mut app.components_with_rect,
+            Rect::new(0, 0, 100, 30),
+            app.tab,
+            &app.popups,
+        );
+        app.handle_mouse_event(MouseEvent {
+            kind: MouseEventKind::Down(MouseButton::Left),
+            column,
+            row,
+            modifiers: crossterm::event::KeyModifiers::empty(),
+        })
+        .unwrap();
+        handle(app);
+    }
+
+    fn rect_of(components: &[ComponentWithRect], slot: Slot) -> Rect {
+        components
+            .iter()
+            .find(|component_info| component_info.slot == slot)
+            .map(|component_info| component_info.rect)
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn test_layout_with_popups() {
+        let mut components = App::generate_components_layout();
+        let area = Rect::new(0, 0, 100, 30);
+        App::update_layout(&mut components, area, Mode::Settings, &[Mode::Confirm]);
+
+        assert_eq!(rect_of(&components, Slot::TabBar), Rect::new(0, 0, 100, 1));
+        assert_eq!(rect_of(&components, Slot::Left), Rect::new(0, 1, 50, 28));
+        assert_eq!(rect_of(&components, Slot::Header), Rect::default());
+        // Centered between the tab bar and the status bar.
+        assert_eq!(
+            rect_of(&components, Slot::Popup(Mode::Confirm)),
+            Rect::new(35, 13, 30, 5)
+        );
+        assert_eq!(
+            rect_of(&components, Slot::Popup(Mode::CommandPalette)),
+            Rect::default()
+        );
+    }
+
+    #[test]
+    fn test_popups_take_keys_and_close_in_order() {
+        let mut app = app(Config::default());
+        send(&mut app, Action::SelectTab(Mode::Counter));
+        send(&mut app, Action::Help);
+        send(&mut app, Action::OpenCommandPalette);
+        assert_eq!(app.popups, [Mode::Help, Mode::CommandPalette]);
+        assert_eq!(app.mode, Mode::CommandPalette);
+
+        // Typed into the palette, rather than closing the help or moving the counter underneath.
+        press(&mut app, KeyCode::Char('q'));
+        press(&mut app, KeyCode::Right);
+        assert_eq!(app.popups, [Mode::Help, Mode::CommandPalette]);
+        assert_eq!(app.state.get_count().unwrap(), 0);
+
+        press(&mut app, KeyCode::Esc);
+        assert_eq!(app.popups, [Mode::Help]);
+        assert_eq!(app.mode, Mode::Help);
+        press(&mut app, KeyCode::Right);
+        assert_eq!(app.state.get_count().unwrap(), 0);
+
+        // Reopening a popup moves it to the top.
+        send(&mut app, Action::OpenCommandPalette);
+        send(&mut app, Action::Help);
+        assert_eq!(app.popups, [Mode::CommandPalette, Mode::Help]);
+        press(&mut app, KeyCode::Char('q'));
+        assert_eq!(app.popups, [Mode::CommandPalette]);
+        press(&mut app, KeyCode::Esc);
+        assert_eq!(app.popups, []);
+        assert_eq!(app.mode, Mode::Counter);
+        assert_eq!(app.state.snapshot().unwrap().mode, Mode::Counter);
+
+        press(&mut app, KeyCode::Right);
+        assert_eq!(app.state.get_count().unwrap(), 1);
+    }
+
+    #[test]
+    fn test_popups_take_mouse_events() {
+        let mut app = app(Config::default());
+        send(&mut app, Action::SelectTab(Mode::Settings));
+        send(&mut app, Action::Help);
+
+        // The keybindings on the right are outside the popup, but covered by it.
+        click(&mut app, 90, 5);
+        assert_eq!(
+            app.focus.get(&Mode::Settings).copied().unwrap_or_default(),
+            0
+        );
+
+        press(&mut app, KeyCode::Esc);
+        click(&mut app, 90, 5);
+        assert_eq!(app.focus.get(&Mode::Settings), Some(&1));
+    }
+
+    #[test]
+    fn test_confirm_configured_action() {
+        let mut config = Config::default();
+        config
+            .config
+            .confirm
+            .actions
+            .insert("incrementby".into(), "Add?".into());
+        let mut app = app(config);
+        send(&mut app, Action::SelectTab(Mode::Counter));
+
+        dispatch(&mut app, Action::IncrementBy(2));
+        assert_eq!(app.popups, [Mode::Confirm]);
+        assert_eq!(app.mode, Mode::Confirm);
+        assert_eq!(app.pending_confirmation, Some(Action::IncrementBy(2)));
+        assert_eq!(app.state.get_count().unwrap(), 0);
+
+        // Another one is rejected rather than replacing the one waiting, and so is a confirmation
+        // of anything else.
+        dispatch(&mut app, Action::IncrementBy(5));
+        send(
+            &mut app,
+            Action::Confirmed(Box::new(Action::IncrementBy(5))),
+        );
+        assert_eq!(app.popups, [Mode::Confirm]);
+        assert_eq!(app.pending_confirmation, Some(Action::IncrementBy(2)));
+        assert_eq!(app.state.get_count().unwrap(), 0);
+
+        // Not asked for, e.g. the steps of a background task.
+        send(&mut app, Action::IncrementBy(3));
+        assert_eq!(app.state.get_count().unwrap(), 3);
+
+        press(&mut app, KeyCode::Char('y'));
+        assert_eq!(app.popups, []);
+        assert_eq!(app.mode, Mode::Counter);
+        assert_eq!(app.pending_confirmation, None);
+        assert_eq!(app.state.get_count().unwrap(), 5);
+        send(
+            &mut app,
+            Action::Confirmed(Box::new(Action::IncrementBy(2))),
+        );
+        assert_eq!(app.state.get_count().unwrap(), 5);
+
+        for key in [KeyCode::Char('n'), KeyCode::Esc] {
+            dispatch(&mut app, Action::IncrementBy(2));
+            assert_eq!(app.popups, [Mode::Confirm]);
+            press(&mut app, key);
+            assert_eq!(app.popups, []);
+            assert_eq!(app.mode, Mode::Counter);
+            assert_eq!(app.pending_confirmation, None);
+            assert_eq!(app.state.get_count().unwrap(), 5);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_quit_confirms_while_tasks_run() {
+        let mut config = Config::default();
+        config.config.confirm_quit = true;
+
+        let mut idle = app(config.clone());
+        dispatch(&mut idle, Action::Quit);
+        assert!(idle.should_quit);
+
+        let mut busy = app(config.clone());
+        send(&mut busy, Action::CountTo(5));
+        dispatch(&mut busy, Action::Quit);
+        assert!(!busy.should_quit);
+        assert_eq!(busy.popups, [Mode::Confirm]);
+        assert_eq!(busy.pending_confirmation, Some(Action::ForceQuit));
+        press(&mut busy, KeyCode::Char('n'));
+        assert!(!busy.should_quit);
+        assert_eq!(busy.popups, []);
+        assert_eq!(busy.mode, Mode::Home);
+
+        // Also when quitting is not asked for directly, but not on a signal.
+        send(&mut busy, Action::Quit);
+        assert_eq!(busy.pending_confirmation, Some(Action::ForceQuit));
+        press(&mut busy, KeyCode::Esc);
+        send(&mut busy, Action::ForceQuit);
+        assert!(busy.should_quit);
+
+        // Asks only once if quitting is configured to be confirmed as well.
+        config
+            .config
+            .confirm
+            .actions
+            .insert("quit".into(), "Quit?".into());
+        let mut idle = app(config.clone());
+        dispatch(&mut idle, Action::Quit);
+        assert_eq!(idle.pending_confirmation, Some(Action::Quit));
+        press(&mut idle, KeyCode::Char('y'));
+        assert!(idle.should_quit);
+
+        let mut busy = app(config);
+        send(&mut busy, Action::CountTo(5));
+        dispatch(&mut busy, Action::Quit);
+        assert_eq!(busy.pending_confirmation, Some(Action::ForceQuit));
+        press(&mut busy, KeyCode::Char('y'));
+        assert!(busy.should_quit);
+    }
+
+    #[tokio::test]
+    async fn test_leaving_the_tab_cancels_counting() {
+        let mut app = app(Config::default());
+        let tasks = app.tasks.handle();
+        send(&mut app, Action::SelectTab(Mode::Counter));
+        send(&mut app, Action::CountTo(5));
+        let id = app.count_task.unwrap();
+        assert_eq!(tasks.running().unwrap(), [(id, "Counting to 5".into())]);
+
+        // Popups are drawn over the tab, which keeps counting.
+        send(&mut app, Action::Help);
+        send(&mut app, Action::ClosePopup);
+        assert_eq!(tasks.running().unwrap().len(), 1);
+
+        send(&mut app, Action::SelectTab(Mode::Home));
+        while !tasks.running().unwrap().is_empty() {
+            tokio::task::yield_now().await;
+        }
+        let mut actions = Vec::new();
+        while let Some(action) = app.action_rx.try_recv() {
+            actions.push(action);
+        }
+        assert_eq!(actions, [Action::TaskCancelled(id)]);
+        assert_eq!(app.state.get_count().unwrap(), 0);
+    }
+}
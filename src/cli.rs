@@ -1,20 +1,137 @@
-use clap::Parser;
+use std::{num::NonZeroUsize, path::PathBuf};
 
-use crate::config::{get_config_dir, get_data_dir};
+use clap::{Parser, Subcommand};
+
+use crate::{
+    action_queue::DEFAULT_QUEUE_CAPACITY,
+    config::{get_config_dir, get_data_dir},
+    headless::OutputFormat,
+    logging::{LogFormat, LogOptions, DEFAULT_LOG_RETENTION_COUNT, DEFAULT_LOG_RETENTION_DAYS},
+    tui::validate_rate,
+};
 
 #[derive(Parser, Debug)]
 #[command(author, version = version(), about)]
 pub struct Cli {
     /// Tick rate, i.e. number of ticks per second
-    #[arg(short, long, value_name = "FLOAT", default_value_t = 4.0)]
+    #[arg(short, long, value_name = "FLOAT", default_value_t = 4.0, value_parser = parse_rate)]
     pub tick_rate: f64,
 
     /// Frame rate, i.e. number of frames per second
-    #[arg(short, long, value_name = "FLOAT", default_value_t = 60.0)]
+    #[arg(short, long, value_name = "FLOAT", default_value_t = 60.0, value_parser = parse_rate)]
     pub frame_rate: f64,
+
+    /// Enable the kitty keyboard protocol if the terminal supports it
+    #[arg(short, long)]
+    pub keyboard_enhancement: bool,
+
+    /// Draw into an inline viewport of HEIGHT lines below the prompt instead of the alternate
+    /// screen, leaving the last frame in the scrollback
+    #[arg(long, value_name = "HEIGHT", value_parser = clap::value_parser!(u16).range(1..))]
+    pub inline: Option<u16>,
+
+    /// Number of actions or events that can be queued before new ones are dropped or wait
+    #[arg(long, value_name = "N", default_value_t = DEFAULT_QUEUE_CAPACITY)]
+    pub queue_capacity: NonZeroUsize,
+
+    /// File with one action per line (JSON or command syntax) to run at startup
+    #[arg(long, value_name = "FILE")]
+    pub commands: Option<PathBuf>,
+
+    /// Keep reading actions line by line from FILE, e.g. a FIFO, or from stdin if FILE is `-`,
+    /// which must then be a pipe or a file rather than the terminal
+    #[arg(long, value_name = "FILE")]
+    pub command_input: Option<PathBuf>,
+
+    /// Serve JSON actions and state queries on a Unix domain socket at PATH
+    #[arg(long, value_name = "PATH")]
+    pub control_socket: Option<PathBuf>,
+
+    /// Log to FILE, appending to it, instead of a new timestamped log in the data directory
+    #[arg(long, value_name = "FILE")]
+    pub log_file: Option<PathBuf>,
+
+    /// Log filter directive such as `debug` or `ratatui_domic=trace`; overrides the
+    /// RATATUI_DOMIC_LOG_LEVEL and RUST_LOG environment variables
+    #[arg(long, value_name = "DIRECTIVE")]
+    pub log_level: Option<String>,
+
+    /// Format of the log file
+    #[arg(long, value_enum, default_value_t)]
+    pub log_format: LogFormat,
+
+    /// Number of session logs to keep in the data directory, including the current one
+    #[arg(long, value_name = "N", default_value_t = DEFAULT_LOG_RETENTION_COUNT, value_parser = parse_log_keep)]
+    pub log_keep: usize,
+
+    /// Delete session logs older than DAYS
+    #[arg(long, value_name = "DAYS", default_value_t = DEFAULT_LOG_RETENTION_DAYS)]
+    pub log_max_age: u64,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Apply actions without starting the UI and print the resulting state
+    Apply {
+        /// Actions (JSON or command syntax) or key sequences such as `<Right><Right>`; read line by
+        /// line from stdin if none are given or the only one is `-`
+        #[arg(value_name = "ACTION")]
+        actions: Vec<String>,
+
+        /// Output format of the resulting state
+        #[arg(long, value_enum, default_value_t)]
+        format: OutputFormat,
+    },
+    /// List or show the crash reports in the data directory
+    Crashes {
+        #[command(subcommand)]
+        command: CrashCommand,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum CrashCommand {
+    /// List the crash reports, oldest first
+    List,
+    /// Show a crash report
+    Show {
+        /// Name of the report as listed; the most recent one if omitted
+        name: Option<String>,
+
+        /// Print the report as JSON
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+impl Cli {
+    pub fn log_options(&self) -> LogOptions {
+        LogOptions {
+            file: self.log_file.clone(),
+            level: self.log_level.clone(),
+            format: self.log_format,
+            retention_count: self.log_keep,
+            retention_days: self.log_max_age,
+        }
+    }
+}
+
+fn parse_log_keep(raw: &str) -> Result<usize, String> {
+    match raw.parse::<usize>().map_err(|err| err.to_string())? {
+        0 => Err("at least the current log is kept".into()),
+        count => Ok(count),
+    }
+}
+
+fn parse_rate(raw: &str) -> Result<f64, String> {
+    let rate = raw.parse::<f64>().map_err(|err| err.to_string())?;
+    validate_rate(rate).map_err(|err| err.to_string())
 }
 
-const VERSION_MESSAGE: &str = concat!(
+pub const VERSION_MESSAGE: &str = concat!(
     env!("CARGO_PKG_VERSION"),
     "-",
     env!("VERGEN_GIT_DESCRIBE"),
@@ -0,0 +1,172 @@
+use std::{
+    fs::File,
+    io::{BufRead, BufReader, IsTerminal},
+    path::{Path, PathBuf},
+};
+
+use color_eyre::{eyre::eyre, Result};
+use tokio::{
+    sync::mpsc::{self, Sender, UnboundedSender},
+    task::JoinHandle,
+};
+use tracing::{debug, error};
+
+use crate::action::Action;
+
+/// Parses one action per line, either as JSON (`{"IncrementBy": 5}`) or in command palette syntax
+/// (`IncrementBy 5`). Blank lines and lines starting with `#` are skipped.
+pub fn parse_commands(text: &str) -> Result<Vec<Action>> {
+    text.lines()
+        .enumerate()
+        .filter_map(|(index, line)| {
+            parse_command_line(line)
+                .map(|action| action.map_err(|err| eyre!("line {}: {err}", index + 1)))
+        })
+        .collect()
+}
+
+fn parse_command_line(line: &str) -> Option<Result<Action, String>> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return None;
+    }
+    Some(line.parse())
+}
+
+/// Reads and parses a startup command file, see [`parse_commands`].
+pub fn read_command_file(path: &Path) -> Result<Vec<Action>> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|err| eyre!("Unable to read commands from {}: {err}", path.display()))?;
+    parse_commands(&text).map_err(|err| eyre!("{}: {err}", path.display()))
+}
+
+/// Checks that command input from `path` can be read alongside the terminal: `-` needs stdin to be
+/// a pipe or a file, since the app reads its key events from the terminal.
+pub fn check_command_input(path: &Path) -> Result<()> {
+    check_stdin(path, std::io::stdin().is_terminal())
+}
+
+fn check_stdin(path: &Path, stdin_is_terminal: bool) -> Result<()> {
+    if path == Path::new("-") && stdin_is_terminal {
+        return Err(eyre!(
+            "Unable to read commands from stdin while it is a terminal; pipe them in instead"
+        ));
+    }
+    Ok(())
+}
+
+/// Spawns a task that reads actions line by line from `path` and sends them on `command_tx`, for
+/// the app to handle like the actions of keys.
+///
+/// `-` reads from stdin, which must not be a terminal, see [`check_command_input`]. A FIFO is reopened whenever its writer closes it, so several scripts can
+/// drive the app one after another; any other file is read once. Lines that fail to parse are
+/// reported as [`Action::Error`]. Reading waits while `command_tx` is full.
+///
+/// The blocking reads happen on a detached thread, since a reader waiting for a FIFO writer or on
+/// stdin would otherwise keep the runtime from shutting down.
+pub fn spawn_command_reader(path: PathBuf, command_tx: Sender<Action>) -> JoinHandle<()> {
+    let (line_tx, mut line_rx) = mpsc::unbounded_channel();
+    let reader_path = path.clone();
+    std::thread::spawn(move || {
+        if let Err(err) = read_command_input(&reader_path, &line_tx) {
+            let _ = line_tx.send(Err(err));
+        }
+    });
+
+    tokio::spawn(async move {
+        while let Some(line) = line_rx.recv().await {
+            let action = match line {
+                Ok(line) => match parse_command_line(&line) {
+                    Some(Ok(action)) => action,
+                    Some(Err(err)) => Action::Error(err),
+                    None => continue,
+                },
+                Err(err) => {
+                    error!("Stopped reading commands from {}: {err}", path.display());
+                    Action::Error(err.to_string())
+                }
+            };
+            debug!("Command input: {action:?}");
+            if command_tx.send(action).await.is_err() {
+                break;
+            }
+        }
+    })
+}
+
+fn read_command_input(path: &Path, line_tx: &UnboundedSender<Result<String>>) -> Result<()> {
+    if path == Path::new("-") {
+        return forward_lines(std::io::stdin().lock(), line_tx);
+    }
+    loop {
+        let file = File::open(path)?;
+        let is_fifo = is_fifo(&file.metadata()?);
+        forward_lines(BufReader::new(file), line_tx)?;
+        if !is_fifo || line_tx.is_closed() {
+            return Ok(());
+        }
+    }
+}
+
+fn forward_lines(reader: impl BufRead, line_tx: &UnboundedSender<Result<String>>) -> Result<()> {
+    for line in reader.lines() {
+        // The receiving task is gone once the app stops, so there is nothing left to do.
+        if line_tx.send(Ok(line?)).is_err() {
+            break;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+fn is_fifo(metadata: &std::fs::Metadata) -> bool {
+    use std::os::unix::fs::FileTypeExt;
+    metadata.file_type().is_fifo()
+}
+
+#[cfg(not(unix))]
+fn is_fifo(_metadata: &std::fs::Metadata) -> bool {
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_commands() {
+        let actions = parse_commands(
+            r#"
+            # pre-seed the counter
+            IncrementBy 5
+            {"DecrementBy": 2}
+
+            OpenCommandPalette
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            actions,
+            vec![
+                Action::IncrementBy(5),
+                Action::DecrementBy(2),
+                Action::OpenCommandPalette
+            ]
+        );
+    }
+
+    #[test]
+    fn test_stdin_must_not_be_a_terminal() {
+        assert!(check_stdin(Path::new("-"), true).is_err());
+        assert!(check_stdin(Path::new("-"), false).is_ok());
+        assert!(check_stdin(Path::new("commands.fifo"), true).is_ok());
+    }
+
+    #[test]
+    fn test_parse_commands_reports_line() {
+        let err = parse_commands("Quit\nIncrementBy x").unwrap_err();
+        assert!(err.to_string().starts_with("line 2:"));
+    }
+}
@@ -1,36 +1,79 @@
 use color_eyre::Result;
+use crossterm::event::{KeyEvent, MouseEvent};
 use ratatui::{
-    layout::{Rect, Size},
+    layout::{Constraint, Rect, Size},
     Frame,
 };
-use tokio::sync::mpsc::UnboundedSender;
 
-use crate::{action::Action, config::Config, state::AppState};
+use crate::{
+    action::Action, action_queue::ActionSender, config::Config, metrics::Metrics, state::AppState,
+    tasks::TaskHandle,
+};
 
+pub mod command_palette;
+pub mod confirm_dialog;
 pub mod counter;
 pub mod fps;
+pub mod help;
 pub mod home;
+pub mod keybindings;
+pub mod log_viewer;
+pub mod settings;
+pub mod status_bar;
+pub mod tab_bar;
 
 /// `Component` is a trait that represents a visual and interactive element of the user interface.
 ///
 /// Implementors of this trait can be registered with the main application loop and will be able to
 /// receive events, update state, and be rendered on the screen.
 pub trait Component {
+    /// A short name identifying the component, e.g. in render timings.
+    fn name(&self) -> &'static str {
+        let path = std::any::type_name::<Self>();
+        path.rsplit("::").next().unwrap_or(path)
+    }
+
     fn register_state_handler(&mut self, state: AppState) -> Result<()> {
         let _ = state; // to appease clippy
         Ok(())
     }
 
+    /// Register a handle to the render and action queue metrics recorded by the app.
+    ///
+    /// # Arguments
+    ///
+    /// * `metrics` - Metrics shared with the app loop.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<()>` - An Ok result or an error.
+    fn register_metrics_handler(&mut self, metrics: Metrics) -> Result<()> {
+        let _ = metrics; // to appease clippy
+        Ok(())
+    }
+    /// Register a handle for spawning background tasks that report back as actions.
+    ///
+    /// # Arguments
+    ///
+    /// * `tasks` - A handle to the app's task manager.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<()>` - An Ok result or an error.
+    fn register_task_handler(&mut self, tasks: TaskHandle) -> Result<()> {
+        let _ = tasks; // to appease clippy
+        Ok(())
+    }
     /// Register an action handler that can send actions for processing if necessary.
     ///
     /// # Arguments
     ///
-    /// * `tx` - An unbounded sender that can send actions.
+    /// * `tx` - A sender that queues actions without blocking.
     ///
     /// # Returns
     ///
     /// * `Result<()>` - An Ok result or an error.
-    fn register_action_handler(&mut self, tx: UnboundedSender<Action>) -> Result<()> {
+    fn register_action_handler(&mut self, tx: ActionSender) -> Result<()> {
         let _ = tx; // to appease clippy
         Ok(())
     }
@@ -60,6 +103,62 @@ pub trait Component {
         let _ = area; // to appease clippy
         Ok(())
     }
+    /// Handle a key event while the component has exclusive input, e.g. an open popup, or has
+    /// the focus and the key is not bound to an action.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - A key event to be processed.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Option<Action>>` - An action to be processed or none.
+    fn handle_key_event(&mut self, key: KeyEvent) -> Result<Option<Action>> {
+        let _ = key; // to appease clippy
+        Ok(None)
+    }
+    /// Handle a mouse event within the tab that shows the component.
+    ///
+    /// # Arguments
+    ///
+    /// * `mouse` - A mouse event to be processed.
+    /// * `area` - The area the component was last drawn in.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Option<Action>>` - An action to be processed or none.
+    fn handle_mouse_event(&mut self, mouse: MouseEvent, area: Rect) -> Result<Option<Action>> {
+        let _ = (mouse, area); // to appease clippy
+        Ok(None)
+    }
+    /// Whether the component can take the focus within its tab, and so receive the key events
+    /// that are not bound to an action.
+    fn focusable(&self) -> bool {
+        false
+    }
+    /// Called when the component gains or loses the focus, including when its tab is left or
+    /// shown again.
+    ///
+    /// # Arguments
+    ///
+    /// * `focused` - Whether the component has the focus now.
+    fn set_focused(&mut self, focused: bool) {
+        let _ = focused; // to appease clippy
+    }
+    /// The width and height of the area a popup is drawn in, centered over the current tab. Only
+    /// asked of components that are placed as popups.
+    ///
+    /// # Returns
+    ///
+    /// * `(Constraint, Constraint)` - The width and the height.
+    fn popup_size(&self) -> (Constraint, Constraint) {
+        (Constraint::Percentage(60), Constraint::Percentage(60))
+    }
+    /// Whether the focused component takes all key events, bypassing the keybindings, e.g. while
+    /// text is being typed into it.
+    fn captures_keys(&self) -> bool {
+        false
+    }
     /// Update the state of the component based on a received action. (REQUIRED)
     ///
     /// # Arguments
@@ -73,6 +172,33 @@ pub trait Component {
         let _ = action; // to appease clippy
         Ok(None)
     }
+    /// Called before the app suspends itself, e.g. on `<Ctrl-z>`, while the terminal is still set
+    /// up.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<()>` - An Ok result or an error.
+    fn on_suspend(&mut self) -> Result<()> {
+        Ok(())
+    }
+    /// Called when the process is continued after being stopped, once the terminal is set up
+    /// again. The terminal size is re-queried and reported as [`Action::Resize`] afterwards.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<()>` - An Ok result or an error.
+    fn on_resume(&mut self) -> Result<()> {
+        Ok(())
+    }
+    /// Release resources and flush state when the app shuts down, after background tasks have
+    /// finished and before the terminal is restored.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<()>` - An Ok result or an error.
+    fn shutdown(&mut self) -> Result<()> {
+        Ok(())
+    }
     /// Render the component on the screen. (REQUIRED)
     ///
     /// # Arguments
@@ -0,0 +1,280 @@
+use color_eyre::Result;
+use crossterm::event::{KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use fuzzy_matcher::{skim::SkimMatcherV2, FuzzyMatcher};
+use ratatui::{
+    layout::{Constraint, Layout, Rect},
+    style::{Style, Stylize},
+    symbols::border,
+    text::{Line, Span},
+    widgets::{Block, Paragraph, Row, Table, TableState},
+    Frame,
+};
+use strum::IntoEnumIterator;
+
+use super::Component;
+use crate::{
+    action::Action,
+    action_queue::ActionSender,
+    config::{key_sequence_to_string, Config},
+};
+
+/// An entry of the command palette.
+#[derive(Debug, Clone, PartialEq)]
+struct Command {
+    /// Variant name of the action, which is what the query is matched against.
+    name: String,
+    /// The action to dispatch, or `None` if the parameters still have to be typed in.
+    action: Option<Action>,
+    /// Key sequences bound to the action, e.g. `<q>, <ctrl-c>`.
+    bindings: String,
+}
+
+/// A popup, opened with `:` by default, that invokes any [`Action`] by name.
+///
+/// Parameterized actions are written in command syntax, e.g. `IncrementBy 5`.
+#[derive(Debug, Clone, Default)]
+pub struct CommandPalette {
+    command_tx: Option<ActionSender>,
+    config: Config,
+    input: String,
+    commands: Vec<Command>,
+    matches: Vec<Command>,
+    selected: usize,
+}
+
+impl CommandPalette {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn bindings_for(&self, matches: impl Fn(&Action) -> bool) -> String {
+        let mut bindings = self
+            .config
+            .keybindings
+            .values()
+            .flat_map(|keymap| keymap.iter())
+            .filter(|(_, action)| matches(action))
+            .map(|(keys, _)| key_sequence_to_string(keys))
+            .collect::<Vec<_>>();
+        bindings.sort();
+        bindings.dedup();
+        bindings.join(", ")
+    }
+
+    fn build_commands(&mut self) {
+        self.commands = Action::iter()
+            .map(|action| {
+                let name = action.to_string();
+                if action.has_parameters() {
+                    let bindings = self.bindings_for(|bound| bound.to_string() == name);
+                    Command {
+                        name,
+                        action: None,
+                        bindings,
+                    }
+                } else {
+                    let bindings = self.bindings_for(|bound| *bound == action);
+                    Command {
+                        name,
+                        action: Some(action),
+                        bindings,
+                    }
+                }
+            })
+            .collect();
+        self.filter();
+    }
+
+    fn filter(&mut self) {
+        let query = self.input.trim_start();
+        let name_query = query.split_whitespace().next().unwrap_or_default();
+        let matcher = SkimMatcherV2::default().ignore_case();
+
+        let mut scored = self
+            .commands
+            .iter()
+            .filter_map(|command| {
+                matcher
+                    .fuzzy_match(&command.name, name_query)
+                    .map(|score| (score, command.clone()))
+            })
+            .collect::<Vec<_>>();
+        // Stable, so entries with equal scores keep the declaration order of `Action`.
+        scored.sort_by(|(a, _), (b, _)| b.cmp(a));
+        self.matches = scored.into_iter().map(|(_, command)| command).collect();
+
+        // Offer the fully typed command, e.g. `IncrementBy 5`, as the first entry.
+        if query.trim_end().contains(char::is_whitespace) {
+            if let Ok(action) = query.parse::<Action>() {
+                let bindings = self.bindings_for(|bound| *bound == action);
+                self.matches.insert(
+                    0,
+                    Command {
+                        name: action.to_command(),
+                        action: Some(action),
+                        bindings,
+                    },
+                );
+            }
+        }
+        self.selected = 0;
+    }
+
+    fn reset(&mut self) {
+        self.input.clear();
+        self.filter();
+    }
+
+    fn complete(&mut self, command: &Command) {
+        self.input = command.name.clone();
+        if command.action.is_none() {
+            self.input.push(' ');
+        }
+        self.filter();
+    }
+
+    fn submit(&mut self) -> Result<Option<Action>> {
+        let Some(command) = self.matches.get(self.selected).cloned() else {
+            return Ok(None);
+        };
+        let Some(action) = command.action else {
+            self.complete(&command);
+            return Ok(None);
+        };
+        // Closed first, and the action is handled like one of a key, e.g. asking for
+        // confirmation.
+        if let Some(tx) = &self.command_tx {
+            tx.send(Action::CloseCommandPalette)?;
+        }
+        Ok(Some(action))
+    }
+}
+
+impl Component for CommandPalette {
+    fn register_action_handler(&mut self, tx: ActionSender) -> Result<()> {
+        self.command_tx = Some(tx);
+        Ok(())
+    }
+
+    fn register_config_handler(&mut self, config: Config) -> Result<()> {
+        self.config = config;
+        self.build_commands();
+        Ok(())
+    }
+
+    fn handle_key_event(&mut self, key: KeyEvent) -> Result<Option<Action>> {
+        if key.kind == KeyEventKind::Release {
+            return Ok(None);
+        }
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Enter => return self.submit(),
+            KeyCode::Up => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Char('p') if ctrl => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Down => {
+                self.selected = (self.selected + 1).min(self.matches.len().saturating_sub(1))
+            }
+            KeyCode::Char('n') if ctrl => {
+                self.selected = (self.selected + 1).min(self.matches.len().saturating_sub(1))
+            }
+            KeyCode::Tab => {
+                if let Some(command) = self.matches.get(self.selected).cloned() {
+                    self.complete(&command);
+                }
+            }
+            KeyCode::Backspace => {
+                self.input.pop();
+                self.filter();
+            }
+            KeyCode::Char(c) if !ctrl => {
+                self.input.push(c);
+                self.filter();
+            }
+            _ => {}
+        }
+        Ok(None)
+    }
+
+    fn update(&mut self, action: Action) -> Result<Option<Action>> {
+        if action == Action::OpenCommandPalette {
+            self.reset();
+        }
+        Ok(None)
+    }
+
+    fn popup_size(&self) -> (Constraint, Constraint) {
+        (Constraint::Percentage(60), Constraint::Length(14))
+    }
+
+    fn draw(&mut self, frame: &mut Frame, area: Rect) -> Result<()> {
+        let block = Block::bordered()
+            .title(Line::from(" Command Palette ".bold()).centered())
+            .border_set(border::ROUNDED);
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        let [input_area, list_area] =
+            Layout::vertical([Constraint::Length(1), Constraint::Min(0)]).areas(inner);
+        let input = Line::from(vec![":".blue().bold(), Span::raw(self.input.as_str())]);
+        frame.render_widget(Paragraph::new(input), input_area);
+        frame.set_cursor_position((
+            input_area.x + 1 + self.input.chars().count() as u16,
+            input_area.y,
+        ));
+
+        let rows = self.matches.iter().map(|command| {
+            let name = if command.action.is_none() {
+                Line::from(vec![Span::raw(command.name.as_str()), " …".dim()])
+            } else {
+                Line::from(command.name.as_str())
+            };
+            Row::new(vec![
+                name,
+                Line::from(command.bindings.as_str().dim()).right_aligned(),
+            ])
+        });
+        let table = Table::new(rows, [Constraint::Fill(1), Constraint::Fill(1)])
+            .row_highlight_style(Style::new().reversed());
+        let mut state = TableState::default().with_selected(Some(self.selected));
+        frame.render_stateful_widget(table, list_area, &mut state);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn palette() -> CommandPalette {
+        let mut palette = CommandPalette::new();
+        palette.build_commands();
+        palette.reset();
+        palette
+    }
+
+    fn type_text(palette: &mut CommandPalette, text: &str) {
+        for c in text.chars() {
+            palette
+                .handle_key_event(KeyEvent::new(KeyCode::Char(c), KeyModifiers::empty()))
+                .unwrap();
+        }
+    }
+
+    #[test]
+    fn test_fuzzy_filter() {
+        let mut palette = palette();
+        type_text(&mut palette, "qt");
+        assert_eq!(palette.matches[0].action, Some(Action::Quit));
+    }
+
+    #[test]
+    fn test_parameterized_command() {
+        let mut palette = palette();
+        type_text(&mut palette, "IncrementBy 5");
+        assert_eq!(palette.matches[0].action, Some(Action::IncrementBy(5)));
+        assert_eq!(palette.matches[1].name, "IncrementBy");
+        assert_eq!(palette.matches[1].action, None);
+    }
+}
@@ -0,0 +1,271 @@
+use color_eyre::Result;
+use crossterm::event::{KeyCode, KeyEvent, KeyEventKind, MouseButton, MouseEvent, MouseEventKind};
+use ratatui::{
+    layout::{Constraint, Flex, Layout, Margin, Position, Rect},
+    style::{Style, Stylize},
+    symbols::border,
+    text::{Line, Span},
+    widgets::{Block, Paragraph, Wrap},
+    Frame,
+};
+
+use super::Component;
+use crate::{
+    action::Action,
+    config::{Config, ConfirmButton},
+};
+
+const YES: &str = " Yes ";
+const NO: &str = " No ";
+/// Narrowest and widest the dialog gets, borders included.
+const MIN_WIDTH: u16 = 30;
+const MAX_WIDTH: u16 = 60;
+
+/// Asks whether to go ahead with the action of an [`Action::Confirm`], and sends it as
+/// [`Action::Confirmed`] on yes or [`Action::CancelConfirm`] on no. The prompt may have several
+/// lines, e.g. the tasks that quitting would cancel. `<y>` and `<n>` answer directly, the arrow keys and `<Tab>` move
+/// between the buttons, and `<Enter>` or a click answers with a button. The button selected at
+/// first is `default_button` in the `confirm` config.
+#[derive(Debug, Clone, Default)]
+pub struct ConfirmDialog {
+    action: Option<Action>,
+    prompt: String,
+    selected: ConfirmButton,
+    default_button: ConfirmButton,
+    /// Where the buttons were last drawn, for mouse clicks.
+    yes_area: Rect,
+    no_area: Rect,
+}
+
+impl ConfirmDialog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn answer(&self, button: ConfirmButton) -> Option<Action> {
+        match button {
+            ConfirmButton::Yes => self
+                .action
+                .clone()
+                .map(|action| Action::Confirmed(Box::new(action))),
+            ConfirmButton::No => Some(Action::CancelConfirm),
+        }
+    }
+
+    fn width(&self) -> u16 {
+        let widest = self
+            .prompt
+            .lines()
+            .map(|line| Line::from(line).width())
+            .max();
+        (widest.unwrap_or_default() as u16 + 4).clamp(MIN_WIDTH, MAX_WIDTH)
+    }
+
+    fn button(&self, label: &'static str, button: ConfirmButton) -> Span<'static> {
+        if self.selected == button {
+            Span::styled(label, Style::new().reversed().bold())
+        } else {
+            Span::from(label)
+        }
+    }
+}
+
+impl Component for ConfirmDialog {
+    fn register_config_handler(&mut self, config: Config) -> Result<()> {
+        self.default_button = config.config.confirm.default_button;
+        Ok(())
+    }
+
+    fn handle_key_event(&mut self, key: KeyEvent) -> Result<Option<Action>> {
+        if key.kind == KeyEventKind::Release {
+            return Ok(None);
+        }
+        match key.code {
+            KeyCode::Char('y') => return Ok(self.answer(ConfirmButton::Yes)),
+            KeyCode::Char('n') => return Ok(self.answer(ConfirmButton::No)),
+            KeyCode::Enter | KeyCode::Char(' ') => return Ok(self.answer(self.selected)),
+            KeyCode::Left | KeyCode::Char('h') => self.selected = ConfirmButton::Yes,
+            KeyCode::Right | KeyCode::Char('l') => self.selected = ConfirmButton::No,
+            KeyCode::Tab | KeyCode::BackTab => {
+                self.selected = match self.selected {
+                    ConfirmButton::Yes => ConfirmButton::No,
+                    ConfirmButton::No => ConfirmButton::Yes,
+                }
+            }
+            _ => {}
+        }
+        Ok(None)
+    }
+
+    fn handle_mouse_event(&mut self, mouse: MouseEvent, _area: Rect) -> Result<Option<Action>> {
+        if mouse.kind != MouseEventKind::Down(MouseButton::Left) {
+            return Ok(None);
+        }
+        let position = Position::new(mouse.column, mouse.row);
+        if self.yes_area.contains(position) {
+            return Ok(self.answer(ConfirmButton::Yes));
+        }
+        if self.no_area.contains(position) {
+            return Ok(self.answer(ConfirmButton::No));
+        }
+        Ok(None)
+    }
+
+    /// Takes the action to confirm. The app rejects another [`Action::Confirm`] while the dialog
+    /// is open, so this never replaces an action that is still waiting.
+    fn update(&mut self, action: Action) -> Result<Option<Action>> {
+        if let Action::Confirm(action, prompt) = action {
+            self.action = Some(*action);
+            self.prompt = prompt;
+            self.selected = self.default_button;
+        }
+        Ok(None)
+    }
+
+    fn popup_size(&self) -> (Constraint, Constraint) {
+        let width = self.width();
+        let prompt_lines = self
+            .prompt
+            .lines()
+            .map(|line| (Line::from(line).width() as u16).div_ceil(width - 4).max(1))
+            .sum::<u16>();
+        // The borders, the prompt, an empty line and the buttons.
+        let height = 2 + prompt_lines.max(1) + 2;
+        (Constraint::Length(width), Constraint::Length(height))
+    }
+
+    fn draw(&mut self, frame: &mut Frame, area: Rect) -> Result<()> {
+        let block = Block::bordered()
+            .title(Line::from(" Confirm ".bold()).centered())
+            .border_set(border::ROUNDED);
+        let inner = block.inner(area).inner(Margin::new(1, 0));
+        frame.render_widget(block, area);
+
+        let [prompt_area, buttons_area] =
+            Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas(inner);
+        frame.render_widget(
+            Paragraph::new(self.prompt.as_str())
+                .centered()
+                .wrap(Wrap { trim: true }),
+            prompt_area,
+        );
+
+        [self.yes_area, self.no_area] = Layout::horizontal([
+            Constraint::Length(YES.len() as u16),
+            Constraint::Length(NO.len() as u16),
+        ])
+        .flex(Flex::Center)
+        .spacing(4)
+        .areas(buttons_area);
+        frame.render_widget(self.button(YES, ConfirmButton::Yes), self.yes_area);
+        frame.render_widget(self.button(NO, ConfirmButton::No), self.no_area);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crossterm::event::KeyModifiers;
+    use pretty_assertions::assert_eq;
+    use ratatui::{backend::TestBackend, Terminal};
+
+    use super::*;
+
+    fn dialog() -> ConfirmDialog {
+        let mut dialog = ConfirmDialog::new();
+        dialog
+            .update(Action::Confirm(Box::new(Action::Quit), "Quit?".into()))
+            .unwrap();
+        dialog
+    }
+
+    fn press(dialog: &mut ConfirmDialog, code: KeyCode) -> Option<Action> {
+        dialog
+            .handle_key_event(KeyEvent::new(code, KeyModifiers::empty()))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_keys() {
+        let confirmed = Some(Action::Confirmed(Box::new(Action::Quit)));
+        let mut dialog = dialog();
+        // No is selected by default.
+        assert_eq!(
+            press(&mut dialog, KeyCode::Enter),
+            Some(Action::CancelConfirm)
+        );
+        assert_eq!(press(&mut dialog, KeyCode::Left), None);
+        assert_eq!(press(&mut dialog, KeyCode::Enter), confirmed);
+        assert_eq!(press(&mut dialog, KeyCode::Tab), None);
+        assert_eq!(
+            press(&mut dialog, KeyCode::Enter),
+            Some(Action::CancelConfirm)
+        );
+        assert_eq!(press(&mut dialog, KeyCode::Char('y')), confirmed);
+
+        dialog.default_button = ConfirmButton::Yes;
+        dialog
+            .update(Action::Confirm(Box::new(Action::Quit), "Quit?".into()))
+            .unwrap();
+        assert_eq!(press(&mut dialog, KeyCode::Enter), confirmed);
+    }
+
+    #[test]
+    fn test_prompt_lines() {
+        let mut dialog = ConfirmDialog::new();
+        let prompt = "Counting to 5 will be cancelled.\nQuit anyway?";
+        dialog
+            .update(Action::Confirm(Box::new(Action::ForceQuit), prompt.into()))
+            .unwrap();
+        assert_eq!(
+            dialog.popup_size(),
+            (Constraint::Length(36), Constraint::Length(6))
+        );
+    }
+
+    #[test]
+    fn test_click() -> Result<()> {
+        let mut dialog = dialog();
+        let (Constraint::Length(width), Constraint::Length(height)) = dialog.popup_size() else {
+            panic!("fixed size expected");
+        };
+        let mut terminal = Terminal::new(TestBackend::new(width, height))?;
+        terminal.draw(|frame| dialog.draw(frame, frame.area()).unwrap())?;
+        let buffer = terminal.backend().buffer();
+        let lines = (0..height)
+            .map(|y| {
+                (0..width)
+                    .map(|x| buffer[(x, y)].symbol())
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            lines,
+            [
+                "╭───────── Confirm ──────────╮",
+                "│            Quit?           │",
+                "│                            │",
+                "│         Yes      No        │",
+                "╰────────────────────────────╯",
+            ]
+        );
+
+        let click = |column, row| MouseEvent {
+            kind: MouseEventKind::Down(MouseButton::Left),
+            column,
+            row,
+            modifiers: KeyModifiers::empty(),
+        };
+        let area = Rect::new(0, 0, width, height);
+        assert_eq!(
+            dialog.handle_mouse_event(click(10, 3), area)?,
+            Some(Action::Confirmed(Box::new(Action::Quit)))
+        );
+        assert_eq!(
+            dialog.handle_mouse_event(click(19, 3), area)?,
+            Some(Action::CancelConfirm)
+        );
+        assert_eq!(dialog.handle_mouse_event(click(15, 3), area)?, None);
+        Ok(())
+    }
+}
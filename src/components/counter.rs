@@ -1,19 +1,24 @@
 use color_eyre::Result;
 use ratatui::{
-    layout::{Alignment, Constraint, Layout, Rect},
+    layout::{Alignment, Constraint, Layout, Margin, Rect},
     style::{Color, Style, Stylize},
     symbols::border,
     text::{Line, Text},
-    widgets::{block::Position, Block, Paragraph},
+    widgets::{block::Position, Block, Gauge, Paragraph},
     Frame,
 };
 
 use super::Component;
-use crate::state::AppState;
+use crate::{
+    config::{Config, CounterConfig},
+    state::AppState,
+};
 
+/// The count and amount, with a gauge showing where the count lies between its bounds.
 #[derive(Debug, Clone)]
 pub struct Counter {
     state: Option<AppState>,
+    counter: CounterConfig,
 }
 
 impl Default for Counter {
@@ -24,7 +29,10 @@ impl Default for Counter {
 
 impl Counter {
     pub fn new() -> Self {
-        Self { state: None }
+        Self {
+            state: None,
+            counter: CounterConfig::default(),
+        }
     }
 }
 
@@ -34,6 +42,11 @@ impl Component for Counter {
         Ok(())
     }
 
+    fn register_config_handler(&mut self, config: Config) -> Result<()> {
+        self.counter = config.config.counter;
+        Ok(())
+    }
+
     fn draw(&mut self, frame: &mut Frame, area: Rect) -> Result<()> {
         let Some(state) = &self.state else {
             return Ok(());
@@ -52,6 +65,8 @@ impl Component for Counter {
             " <Left> |".blue().bold(),
             decrease_by_interpolation.into(),
             " <Right> |".blue().bold(),
+            " Reset".into(),
+            " <R> |".blue().bold(),
             " Quit ".into(),
             "<Q> ".blue().bold(),
         ]);
@@ -92,14 +107,26 @@ impl Component for Counter {
         let vertical_left = Layout::vertical([Constraint::Length(3)]);
         let vertical_right = Layout::vertical([
             Constraint::Percentage(50),
-            Constraint::Length(10),
+            Constraint::Length(2),
+            Constraint::Length(3),
             Constraint::Percentage(50),
         ]);
         let chunks_amount = vertical_left.split(chunks_inner_area[0]);
         let chunks_count = vertical_right.split(chunks_inner_area[1]);
 
+        let bounds_block = Block::bordered()
+            .title(Line::from(format!(" {} ", self.counter.min)).left_aligned())
+            .title(Line::from(format!(" {} ", self.counter.max)).right_aligned())
+            .border_set(border::ROUNDED);
+        let gauge = Gauge::default()
+            .block(bounds_block)
+            .gauge_style(Style::default().fg(Color::Yellow))
+            .ratio(self.counter.ratio(count))
+            .label(format!("{:?}", self.counter.overflow));
+
         frame.render_widget(amount_paragraph, chunks_amount[0]);
         frame.render_widget(count_paragraph, chunks_count[1]);
+        frame.render_widget(gauge, chunks_count[2].inner(Margin::new(2, 0)));
         Ok(())
     }
 }
@@ -1,20 +1,30 @@
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 use color_eyre::Result;
 use ratatui::{
     layout::{Constraint, Layout, Rect},
     style::{Style, Stylize},
-    text::Span,
-    widgets::Paragraph,
+    text::{Line, Span},
+    widgets::{Paragraph, Sparkline},
     Frame,
 };
 
 use super::Component;
 
-use crate::action::Action;
+use crate::{action::Action, metrics::Metrics};
 
-#[derive(Debug, Clone, PartialEq)]
+/// Width of the overlay drawn in the top right corner of the component's area.
+const OVERLAY_WIDTH: u16 = 52;
+/// Height of the frame time sparkline.
+const SPARKLINE_HEIGHT: u16 = 3;
+
+/// Overlay with tick and frame rates, frame time percentiles, per-component draw times, the action
+/// queue depth and a frame time sparkline. Toggled with [`Action::ToggleFpsOverlay`].
+#[derive(Debug, Clone)]
 pub struct FpsCounter {
+    visible: bool,
+    metrics: Metrics,
+
     last_tick_update: Instant,
     tick_count: u32,
     ticks_per_second: f64,
@@ -33,6 +43,8 @@ impl Default for FpsCounter {
 impl FpsCounter {
     pub fn new() -> Self {
         Self {
+            visible: true,
+            metrics: Metrics::default(),
             last_tick_update: Instant::now(),
             tick_count: 0,
             ticks_per_second: 0.0,
@@ -42,7 +54,8 @@ impl FpsCounter {
         }
     }
 
-    fn app_tick(&mut self) -> Result<()> {
+    /// Returns whether the ticks per second were recalculated.
+    fn app_tick(&mut self) -> Result<bool> {
         self.tick_count += 1;
         let now = Instant::now();
         let elapsed = (now - self.last_tick_update).as_secs_f64();
@@ -50,8 +63,9 @@ impl FpsCounter {
             self.ticks_per_second = self.tick_count as f64 / elapsed;
             self.last_tick_update = now;
             self.tick_count = 0;
+            return Ok(true);
         }
-        Ok(())
+        Ok(false)
     }
 
     fn render_tick(&mut self) -> Result<()> {
@@ -68,24 +82,120 @@ impl FpsCounter {
 }
 
 impl Component for FpsCounter {
+    fn register_metrics_handler(&mut self, metrics: Metrics) -> Result<()> {
+        self.metrics = metrics;
+        Ok(())
+    }
+
     fn update(&mut self, action: Action) -> Result<Option<Action>> {
         match action {
-            Action::Tick => self.app_tick()?,
-            Action::Render => self.render_tick()?,
-            _ => {}
-        };
-        Ok(None)
+            // Frames are counted in `draw`, since renders only happen when something changed.
+            Action::Tick if self.app_tick()? && self.visible => Ok(Some(Action::Render)),
+            Action::ToggleFpsOverlay => {
+                self.visible = !self.visible;
+                Ok(None)
+            }
+            _ => Ok(None),
+        }
     }
 
     fn draw(&mut self, frame: &mut Frame, area: Rect) -> Result<()> {
-        let [top, _] = Layout::vertical([Constraint::Length(1), Constraint::Min(0)]).areas(area);
-        let message = format!(
-            "{:.2} ticks/sec, {:.2} FPS",
-            self.ticks_per_second, self.frames_per_second
-        );
-        let span = Span::styled(message, Style::new().dim());
-        let paragraph = Paragraph::new(span).right_aligned();
-        frame.render_widget(paragraph, top);
+        self.render_tick()?;
+        if !self.visible {
+            return Ok(());
+        }
+        let metrics = self.metrics.snapshot();
+        let percentile = |percentile| {
+            metrics
+                .frame_time_percentile(percentile)
+                .map_or("-".to_string(), format_duration)
+        };
+
+        let mut lines = vec![
+            Line::from(format!(
+                "{:.2} ticks/sec, {:.2} FPS",
+                self.ticks_per_second, self.frames_per_second
+            )),
+            Line::from(format!(
+                "frame p50 {} p95 {} p99 {}",
+                percentile(50.0),
+                percentile(95.0),
+                percentile(99.0)
+            )),
+            Line::from(format!(
+                "queue {} (max {})",
+                metrics.queue_depth, metrics.max_queue_depth
+            )),
+        ];
+        lines.extend(metrics.draw_times.iter().map(|(name, duration)| {
+            Line::from(vec![
+                Span::raw(format!("{name} ")),
+                Span::raw(format_duration(*duration)).bold(),
+            ])
+        }));
+
+        let [_, overlay] =
+            Layout::horizontal([Constraint::Fill(1), Constraint::Max(OVERLAY_WIDTH)]).areas(area);
+        let [text_area, sparkline_area] = Layout::vertical([
+            Constraint::Length(lines.len() as u16),
+            Constraint::Length(SPARKLINE_HEIGHT),
+        ])
+        .areas(overlay);
+        let paragraph = Paragraph::new(lines)
+            .style(Style::new().dim())
+            .right_aligned();
+        frame.render_widget(paragraph, text_area);
+
+        // Most recent frames on the right, in microseconds.
+        let history = metrics
+            .frame_times
+            .iter()
+            .rev()
+            .take(sparkline_area.width as usize)
+            .rev()
+            .map(|duration| duration.as_micros() as u64)
+            .collect::<Vec<_>>();
+        let sparkline = Sparkline::default()
+            .data(&history)
+            .style(Style::new().dim());
+        frame.render_widget(sparkline, sparkline_area);
         Ok(())
     }
 }
+
+fn format_duration(duration: Duration) -> String {
+    format!("{:.2}ms", duration.as_secs_f64() * 1000.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::{backend::TestBackend, Terminal};
+
+    use super::*;
+
+    fn render(fps: &mut FpsCounter) -> String {
+        let mut terminal = Terminal::new(TestBackend::new(60, 10)).unwrap();
+        terminal
+            .draw(|frame| fps.draw(frame, frame.area()).unwrap())
+            .unwrap();
+        format!("{:?}", terminal.backend().buffer())
+    }
+
+    #[test]
+    fn test_overlay() {
+        let mut fps = FpsCounter::new();
+        fps.metrics.record_frame(
+            Duration::from_millis(2),
+            vec![("Home".to_string(), Duration::ZERO)],
+        );
+        fps.metrics.record_queue_depth(4);
+
+        let screen = render(&mut fps);
+        assert!(screen.contains("frame p50 2.00ms p95 2.00ms p99 2.00ms"));
+        assert!(screen.contains("queue 4 (max 4)"));
+        assert!(screen.contains("Home 0.00ms"));
+
+        fps.update(Action::ToggleFpsOverlay).unwrap();
+        assert!(!render(&mut fps).contains("FPS"));
+    }
+}
@@ -0,0 +1,123 @@
+use color_eyre::Result;
+use crossterm::event::{KeyCode, KeyEvent, KeyEventKind};
+use ratatui::{
+    layout::{Constraint, Rect},
+    style::Stylize,
+    symbols::border,
+    text::Line,
+    widgets::{Block, Paragraph},
+    Frame,
+};
+
+use super::{
+    keybindings::{binding_lines, KEYS_WIDTH},
+    settings::scroll,
+    Component,
+};
+use crate::{action::Action, app::Mode, config::Config, state::AppState};
+
+/// Keys handled by [`EventHandler`](crate::event_handler::EventHandler) in the counter tabs,
+/// which are not part of the keybindings.
+const COUNTER_KEYS: [(&str, &str); 3] = [
+    (
+        "<left> <right>",
+        "Decrement or increment the count by the amount",
+    ),
+    ("<up> <down>", "Increment or decrement the amount"),
+    ("<esc>", "Quit"),
+];
+
+/// A popup, opened with [`Action::Help`], with the keybindings of the tab underneath it.
+#[derive(Debug, Clone, Default)]
+pub struct Help {
+    state: Option<AppState>,
+    config: Config,
+    /// The tab underneath, as of the last action handled while no popup was open.
+    tab: Mode,
+    scroll: usize,
+    /// Number of lines that fit on screen, as of the last draw.
+    page_height: usize,
+}
+
+impl Help {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn lines(&self) -> Vec<Line<'static>> {
+        let mut lines = self
+            .config
+            .keybindings
+            .get(&self.tab)
+            .map(binding_lines)
+            .unwrap_or_default();
+        if self.tab.has_counter_keys() {
+            lines.extend(COUNTER_KEYS.iter().map(|(keys, what)| {
+                Line::from(vec![format!("  {keys:KEYS_WIDTH$}").blue(), (*what).into()])
+            }));
+        }
+        lines.push(Line::from(""));
+        lines.push(Line::from("  <esc> closes the topmost popup".dim()));
+        lines
+    }
+}
+
+impl Component for Help {
+    fn register_state_handler(&mut self, state: AppState) -> Result<()> {
+        self.state = Some(state);
+        Ok(())
+    }
+
+    fn register_config_handler(&mut self, config: Config) -> Result<()> {
+        self.config = config;
+        Ok(())
+    }
+
+    fn handle_key_event(&mut self, key: KeyEvent) -> Result<Option<Action>> {
+        if key.kind == KeyEventKind::Release {
+            return Ok(None);
+        }
+        if key.code == KeyCode::Char('q') {
+            return Ok(Some(Action::ClosePopup));
+        }
+        scroll(&mut self.scroll, key, self.page_height);
+        Ok(None)
+    }
+
+    fn update(&mut self, action: Action) -> Result<Option<Action>> {
+        if let Some(state) = &self.state {
+            let mode = state.snapshot()?.mode;
+            if !mode.is_overlay() {
+                self.tab = mode;
+            }
+        }
+        if action == Action::Help {
+            self.scroll = 0;
+        }
+        Ok(None)
+    }
+
+    fn popup_size(&self) -> (Constraint, Constraint) {
+        // Room for the lines and the borders.
+        let height = self.lines().len() as u16 + 2;
+        (Constraint::Percentage(60), Constraint::Length(height))
+    }
+
+    fn draw(&mut self, frame: &mut Frame, area: Rect) -> Result<()> {
+        let lines = self.lines();
+        let block = Block::bordered()
+            .title(Line::from(format!(" Help: {} ", self.tab.title()).bold()).centered())
+            .border_set(border::ROUNDED);
+        self.page_height = block.inner(area).height as usize;
+        self.scroll = self
+            .scroll
+            .min(lines.len().saturating_sub(self.page_height));
+        frame.render_widget(
+            Paragraph::new(lines)
+                .block(block)
+                .scroll((self.scroll as u16, 0)),
+            area,
+        );
+        Ok(())
+    }
+}
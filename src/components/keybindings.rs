@@ -0,0 +1,140 @@
+use std::collections::{BTreeMap, HashMap};
+
+use color_eyre::Result;
+use crossterm::event::{KeyEvent, KeyEventKind};
+use ratatui::{layout::Rect, style::Stylize, text::Line, widgets::Paragraph, Frame};
+
+use super::{
+    settings::{focus_block, scroll},
+    Component,
+};
+use crate::{
+    action::Action,
+    config::{key_sequence_to_string, Config},
+};
+
+/// Width of the key sequence column.
+pub(super) const KEYS_WIDTH: usize = 16;
+
+/// The keybindings of every mode, in command syntax. Scrolls with the arrow keys while focused.
+#[derive(Debug, Clone, Default)]
+pub struct Keybindings {
+    lines: Vec<Line<'static>>,
+    focused: bool,
+    scroll: usize,
+    /// Number of lines that fit on screen, as of the last draw.
+    page_height: usize,
+}
+
+impl Keybindings {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+/// A line per binding, sorted by key sequence.
+pub(super) fn binding_lines(bindings: &HashMap<Vec<KeyEvent>, Action>) -> Vec<Line<'static>> {
+    bindings
+        .iter()
+        .map(|(keys, action)| (key_sequence_to_string(keys), action.to_command()))
+        .collect::<BTreeMap<_, _>>()
+        .into_iter()
+        .map(|(keys, action)| {
+            Line::from(vec![format!("  {keys:KEYS_WIDTH$}").blue(), action.into()])
+        })
+        .collect()
+}
+
+/// A heading per mode followed by its bindings, sorted by name.
+fn describe(config: &Config) -> Vec<Line<'static>> {
+    let modes = config
+        .keybindings
+        .iter()
+        .map(|(mode, bindings)| (format!("{mode:?}"), bindings))
+        .collect::<BTreeMap<_, _>>();
+    let mut lines = Vec::new();
+    for (mode, bindings) in modes {
+        if !lines.is_empty() {
+            lines.push(Line::from(""));
+        }
+        lines.push(Line::from(mode.bold()));
+        lines.extend(binding_lines(bindings));
+    }
+    lines
+}
+
+impl Component for Keybindings {
+    fn register_config_handler(&mut self, config: Config) -> Result<()> {
+        self.lines = describe(&config);
+        Ok(())
+    }
+
+    fn focusable(&self) -> bool {
+        true
+    }
+
+    fn set_focused(&mut self, focused: bool) {
+        self.focused = focused;
+    }
+
+    fn handle_key_event(&mut self, key: KeyEvent) -> Result<Option<Action>> {
+        if key.kind != KeyEventKind::Release {
+            scroll(&mut self.scroll, key, self.page_height);
+        }
+        Ok(None)
+    }
+
+    fn draw(&mut self, frame: &mut Frame, area: Rect) -> Result<()> {
+        let block = focus_block("Keybindings", self.focused);
+        self.page_height = block.inner(area).height as usize;
+        self.scroll = self
+            .scroll
+            .min(self.lines.len().saturating_sub(self.page_height));
+        frame.render_widget(
+            Paragraph::new(self.lines.clone())
+                .block(block)
+                .scroll((self.scroll as u16, 0)),
+            area,
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::app::Mode;
+
+    #[test]
+    fn test_describe() {
+        let mut config = Config::default();
+        for (mode, keys, action) in [
+            (Mode::Settings, "<q>", Action::Quit),
+            (Mode::Home, "<Tab>", Action::NextTab),
+            (Mode::Home, "<1>", Action::SelectTab(Mode::Home)),
+        ] {
+            config
+                .keybindings
+                .entry(mode)
+                .or_default()
+                .insert(crate::config::parse_key_sequence(keys).unwrap(), action);
+        }
+        let lines = describe(&config)
+            .iter()
+            .map(|line| line.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            lines,
+            [
+                "Home",
+                "  <1>             SelectTab Home",
+                "  <tab>           NextTab",
+                "",
+                "Settings",
+                "  <q>             Quit",
+            ]
+        );
+    }
+}
@@ -0,0 +1,396 @@
+use color_eyre::Result;
+use crossterm::event::{KeyCode, KeyEvent, KeyEventKind, KeyModifiers, MouseEvent, MouseEventKind};
+use ratatui::{
+    layout::Rect,
+    style::{Color, Style, Stylize},
+    symbols::border,
+    text::{Line, Span},
+    widgets::{Block, Paragraph},
+    Frame,
+};
+use tracing::Level;
+
+use super::Component;
+use crate::{
+    action::Action,
+    logging::{
+        buffer::{LogBuffer, LogRecord},
+        LOG_BUFFER,
+    },
+};
+
+/// Levels in the order that `l` cycles through them.
+const LEVELS: [Level; 5] = [
+    Level::ERROR,
+    Level::WARN,
+    Level::INFO,
+    Level::DEBUG,
+    Level::TRACE,
+];
+
+/// Which filter is being typed into.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Input {
+    Search,
+    Target,
+}
+
+/// The most recent log events, shown in the logs tab.
+///
+/// `l`/`L` raise and lower the most verbose level shown, `t` filters by target and `/` searches the
+/// messages; both are substring matches, the search ignoring case. `f` toggles following the newest
+/// events, which scrolling up turns off and `G` turns back on. `q` and `<Esc>` go back to the
+/// previous tab.
+#[derive(Debug, Clone)]
+pub struct LogViewer {
+    buffer: LogBuffer,
+    focused: bool,
+    max_level: Level,
+    target: String,
+    search: String,
+    input: Option<Input>,
+    follow: bool,
+    /// Index of the first visible record among the filtered ones, while not following.
+    scroll: usize,
+    /// Number of records that fit on screen, as of the last draw.
+    page_height: usize,
+    /// Buffer generation as of the last draw.
+    drawn_generation: u64,
+}
+
+impl Default for LogViewer {
+    fn default() -> Self {
+        Self::new(LOG_BUFFER.clone())
+    }
+}
+
+impl LogViewer {
+    pub fn new(buffer: LogBuffer) -> Self {
+        Self {
+            buffer,
+            focused: false,
+            max_level: Level::TRACE,
+            target: String::new(),
+            search: String::new(),
+            input: None,
+            follow: true,
+            scroll: 0,
+            page_height: 0,
+            drawn_generation: 0,
+        }
+    }
+
+    fn matches(&self, record: &LogRecord) -> bool {
+        record.level <= self.max_level
+            && record.target.contains(&self.target)
+            && record
+                .message
+                .to_lowercase()
+                .contains(&self.search.to_lowercase())
+    }
+
+    fn filtered(&self) -> Vec<LogRecord> {
+        self.buffer
+            .records()
+            .into_iter()
+            .filter(|record| self.matches(record))
+            .collect()
+    }
+
+    /// Index of the first visible record out of `len`.
+    fn offset(&self, len: usize) -> usize {
+        let last_page = len.saturating_sub(self.page_height);
+        if self.follow {
+            last_page
+        } else {
+            self.scroll.min(last_page)
+        }
+    }
+
+    fn scroll_up(&mut self, lines: usize) {
+        self.scroll = self.offset(self.filtered().len()).saturating_sub(lines);
+        self.follow = false;
+    }
+
+    fn scroll_down(&mut self, lines: usize) {
+        let len = self.filtered().len();
+        self.scroll = self.offset(len).saturating_add(lines);
+        // Scrolling to the bottom follows again.
+        self.follow = self.scroll + self.page_height >= len;
+    }
+
+    fn cycle_level(&mut self, step: isize) {
+        let index = LEVELS
+            .iter()
+            .position(|level| *level == self.max_level)
+            .unwrap_or_default();
+        let index = (index as isize + step).rem_euclid(LEVELS.len() as isize);
+        self.max_level = LEVELS[index as usize];
+    }
+
+    fn handle_input_key(&mut self, input: Input, key: KeyEvent) {
+        let text = match input {
+            Input::Search => &mut self.search,
+            Input::Target => &mut self.target,
+        };
+        match key.code {
+            KeyCode::Enter => self.input = None,
+            KeyCode::Esc => {
+                text.clear();
+                self.input = None;
+            }
+            KeyCode::Backspace => {
+                text.pop();
+            }
+            KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => text.push(c),
+            _ => {}
+        }
+    }
+
+    fn status_line(&self) -> Line<'static> {
+        let mut spans = vec![format!(" level ≤ {} ", self.max_level).into()];
+        let filter = |label: &str, text: &str, input| {
+            let editing = self.input == Some(input);
+            if text.is_empty() && !editing {
+                return None;
+            }
+            let cursor = if editing { "█" } else { "" };
+            Some(Span::from(format!("│ {label}: {text}{cursor} ")))
+        };
+        spans.extend(filter("target", &self.target, Input::Target));
+        spans.extend(filter("search", &self.search, Input::Search));
+        if self.follow {
+            spans.push("│ follow ".bold());
+        }
+        Line::from(spans)
+    }
+}
+
+/// Lines scrolled per mouse wheel step.
+const WHEEL_LINES: usize = 3;
+
+impl Component for LogViewer {
+    fn focusable(&self) -> bool {
+        true
+    }
+
+    fn set_focused(&mut self, focused: bool) {
+        self.focused = focused;
+        self.input = None;
+    }
+
+    fn captures_keys(&self) -> bool {
+        self.input.is_some()
+    }
+
+    fn handle_key_event(&mut self, key: KeyEvent) -> Result<Option<Action>> {
+        if key.kind == KeyEventKind::Release {
+            return Ok(None);
+        }
+        if let Some(input) = self.input {
+            self.handle_input_key(input, key);
+            return Ok(None);
+        }
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => return Ok(Some(Action::ToggleLogViewer)),
+            KeyCode::Char('l') => self.cycle_level(-1),
+            KeyCode::Char('L') => self.cycle_level(1),
+            KeyCode::Char('/') => self.input = Some(Input::Search),
+            KeyCode::Char('t') => self.input = Some(Input::Target),
+            KeyCode::Char('f') => {
+                self.scroll = self.offset(self.filtered().len());
+                self.follow = !self.follow;
+            }
+            KeyCode::Up | KeyCode::Char('k') => self.scroll_up(1),
+            KeyCode::Down | KeyCode::Char('j') => self.scroll_down(1),
+            KeyCode::PageUp => self.scroll_up(self.page_height.max(1)),
+            KeyCode::PageDown => self.scroll_down(self.page_height.max(1)),
+            KeyCode::Home | KeyCode::Char('g') => {
+                self.scroll = 0;
+                self.follow = false;
+            }
+            KeyCode::End | KeyCode::Char('G') => self.follow = true,
+            _ => {}
+        }
+        Ok(None)
+    }
+
+    fn handle_mouse_event(&mut self, mouse: MouseEvent, _area: Rect) -> Result<Option<Action>> {
+        match mouse.kind {
+            MouseEventKind::ScrollUp => self.scroll_up(WHEEL_LINES),
+            MouseEventKind::ScrollDown => self.scroll_down(WHEEL_LINES),
+            _ => {}
+        }
+        Ok(None)
+    }
+
+    fn update(&mut self, action: Action) -> Result<Option<Action>> {
+        // Only shown, and so only redrawn, while it has the focus in the logs tab.
+        if action == Action::Tick
+            && self.focused
+            && self.buffer.generation() != self.drawn_generation
+        {
+            return Ok(Some(Action::Render));
+        }
+        Ok(None)
+    }
+
+    fn draw(&mut self, frame: &mut Frame, area: Rect) -> Result<()> {
+        self.drawn_generation = self.buffer.generation();
+        let records = self.filtered();
+        let block = Block::bordered()
+            .title(Line::from(" Logs ".bold()).centered())
+            .title_bottom(self.status_line())
+            .border_set(border::ROUNDED);
+        self.page_height = block.inner(area).height as usize;
+        let offset = self.offset(records.len());
+
+        let lines = records
+            .iter()
+            .skip(offset)
+            .take(self.page_height)
+            .map(|record| self.format_record(record))
+            .collect::<Vec<_>>();
+        frame.render_widget(Paragraph::new(lines).block(block), area);
+        Ok(())
+    }
+}
+
+impl LogViewer {
+    fn format_record(&self, record: &LogRecord) -> Line<'static> {
+        // Local time, like the clock in the status bar.
+        let time = jiff::Zoned::try_from(record.time)
+            .map(|time| time.strftime("%H:%M:%S").to_string())
+            .unwrap_or_default();
+        let level_color = match record.level {
+            Level::ERROR => Color::Red,
+            Level::WARN => Color::Yellow,
+            Level::INFO => Color::Green,
+            Level::DEBUG => Color::Blue,
+            Level::TRACE => Color::Magenta,
+        };
+        let mut spans = vec![
+            Span::from(format!("{time} ")).dim(),
+            Span::styled(format!("{:5} ", record.level), level_color),
+            Span::from(format!("{} ", record.target)).dim(),
+        ];
+        spans.extend(highlight(&record.message, &self.search));
+        Line::from(spans)
+    }
+}
+
+/// Splits `text` into spans with the case-insensitive matches of `search` highlighted.
+fn highlight(text: &str, search: &str) -> Vec<Span<'static>> {
+    let lowercase = text.to_lowercase();
+    // Lowercasing can change byte lengths, in which case matches are not highlighted.
+    if search.is_empty() || lowercase.len() != text.len() {
+        return vec![Span::from(text.to_string())];
+    }
+    let search = search.to_lowercase();
+    let mut spans = Vec::new();
+    let mut start = 0;
+    for (index, matched) in lowercase.match_indices(&search) {
+        spans.push(Span::from(text[start..index].to_string()));
+        spans.push(Span::styled(
+            text[index..index + matched.len()].to_string(),
+            Style::new().reversed(),
+        ));
+        start = index + matched.len();
+    }
+    spans.push(Span::from(text[start..].to_string()));
+    spans
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::SystemTime;
+
+    use pretty_assertions::assert_eq;
+    use ratatui::{backend::TestBackend, Terminal};
+
+    use super::*;
+
+    fn viewer() -> LogViewer {
+        let buffer = LogBuffer::new(100);
+        for (index, level) in LEVELS.iter().cycle().take(20).enumerate() {
+            buffer.push(LogRecord {
+                time: SystemTime::UNIX_EPOCH,
+                level: *level,
+                target: if index % 2 == 0 { "app" } else { "tui" }.into(),
+                message: format!("message {index}"),
+            });
+        }
+        let mut viewer = LogViewer::new(buffer);
+        viewer.set_focused(true);
+        viewer
+    }
+
+    fn press(viewer: &mut LogViewer, keys: &str) {
+        for c in keys.chars() {
+            let code = match c {
+                '\n' => KeyCode::Enter,
+                '↑' => KeyCode::Up,
+                c => KeyCode::Char(c),
+            };
+            viewer
+                .handle_key_event(KeyEvent::new(code, KeyModifiers::empty()))
+                .unwrap();
+        }
+    }
+
+    fn visible_messages(viewer: &mut LogViewer) -> Vec<String> {
+        let mut terminal = Terminal::new(TestBackend::new(60, 6)).unwrap();
+        terminal
+            .draw(|frame| viewer.draw(frame, frame.area()).unwrap())
+            .unwrap();
+        let records = viewer.filtered();
+        let offset = viewer.offset(records.len());
+        records
+            .into_iter()
+            .skip(offset)
+            .take(viewer.page_height)
+            .map(|record| record.message)
+            .collect()
+    }
+
+    #[test]
+    fn test_follow_and_scroll() {
+        let mut viewer = viewer();
+        assert_eq!(
+            visible_messages(&mut viewer),
+            ["message 16", "message 17", "message 18", "message 19"]
+        );
+        press(&mut viewer, "↑↑");
+        assert!(!viewer.follow);
+        assert_eq!(visible_messages(&mut viewer)[0], "message 14");
+        press(&mut viewer, "G");
+        assert_eq!(visible_messages(&mut viewer)[0], "message 16");
+    }
+
+    #[test]
+    fn test_filters() {
+        let mut viewer = viewer();
+        // ERROR and WARN only
+        press(&mut viewer, "lll");
+        press(&mut viewer, "tapp\n");
+        assert_eq!(
+            visible_messages(&mut viewer),
+            ["message 0", "message 6", "message 10", "message 16"]
+        );
+        press(&mut viewer, "/MESSAGE 1\n");
+        assert_eq!(visible_messages(&mut viewer), ["message 10", "message 16"]);
+        assert_eq!(viewer.max_level, Level::WARN);
+    }
+
+    #[test]
+    fn test_highlight() {
+        let spans = highlight("Action Tick, action Render", "action");
+        assert_eq!(
+            spans
+                .iter()
+                .map(|span| span.content.as_ref())
+                .collect::<Vec<_>>(),
+            ["", "Action", " Tick, ", "action", " Render"]
+        );
+    }
+}
@@ -0,0 +1,148 @@
+use color_eyre::Result;
+use crossterm::event::{KeyCode, KeyEvent, KeyEventKind};
+use ratatui::{
+    layout::Rect,
+    style::{Style, Stylize},
+    symbols::border,
+    text::Line,
+    widgets::{Block, Paragraph},
+    Frame,
+};
+
+use super::Component;
+use crate::{action::Action, config::Config, logging};
+
+/// Width of the name column.
+const NAME_WIDTH: usize = 18;
+
+/// The settings in effect: the directories and log file in use, the log filter and the app config.
+/// Scrolls with the arrow keys while focused.
+#[derive(Debug, Clone, Default)]
+pub struct Settings {
+    config: Config,
+    focused: bool,
+    scroll: usize,
+    /// Number of lines that fit on screen, as of the last draw.
+    page_height: usize,
+}
+
+impl Settings {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn lines(&self) -> Vec<Line<'static>> {
+        let config = &self.config.config;
+        let status_bar = &config.status_bar;
+        let counter = &config.counter;
+        let settings = [
+            ("Config directory", config.config_dir.display().to_string()),
+            ("Data directory", config.data_dir.display().to_string()),
+            (
+                "Log file",
+                logging::log_path().map_or("none".into(), |path| path.display().to_string()),
+            ),
+            ("Log level", logging::level().unwrap_or_default()),
+            ("Confirm quit", config.confirm_quit.to_string()),
+            (
+                "Confirm actions",
+                config
+                    .confirm
+                    .actions
+                    .keys()
+                    .cloned()
+                    .collect::<std::collections::BTreeSet<_>>()
+                    .into_iter()
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            ),
+            (
+                "Default button",
+                format!("{:?}", config.confirm.default_button),
+            ),
+            (
+                "Counter bounds",
+                format!("{}..={} ({:?})", counter.min, counter.max, counter.overflow),
+            ),
+            (
+                "Inline height",
+                config
+                    .inline_height
+                    .map_or("none".into(), |height| height.to_string()),
+            ),
+            ("Status bar left", format!("{:?}", status_bar.left)),
+            ("Status bar center", format!("{:?}", status_bar.center)),
+            ("Status bar right", format!("{:?}", status_bar.right)),
+        ];
+        settings
+            .into_iter()
+            .map(|(name, value)| {
+                Line::from(vec![format!("{name:NAME_WIDTH$}").bold(), value.into()])
+            })
+            .collect()
+    }
+}
+
+/// A bordered block with `title`, highlighted while focused.
+pub(super) fn focus_block(title: &str, focused: bool) -> Block<'static> {
+    let block = Block::bordered().title(Line::from(format!(" {title} ").bold()).centered());
+    if focused {
+        block
+            .border_set(border::THICK)
+            .border_style(Style::new().cyan())
+    } else {
+        block.border_set(border::ROUNDED)
+    }
+}
+
+/// Moves `offset` for the arrow, page, home and end keys, and `j`, `k`, `g` and `G`.
+pub(super) fn scroll(offset: &mut usize, key: KeyEvent, page_height: usize) {
+    let page_height = page_height.max(1);
+    match key.code {
+        KeyCode::Up | KeyCode::Char('k') => *offset = offset.saturating_sub(1),
+        KeyCode::Down | KeyCode::Char('j') => *offset = offset.saturating_add(1),
+        KeyCode::PageUp => *offset = offset.saturating_sub(page_height),
+        KeyCode::PageDown => *offset = offset.saturating_add(page_height),
+        KeyCode::Home | KeyCode::Char('g') => *offset = 0,
+        KeyCode::End | KeyCode::Char('G') => *offset = usize::MAX,
+        _ => {}
+    }
+}
+
+impl Component for Settings {
+    fn register_config_handler(&mut self, config: Config) -> Result<()> {
+        self.config = config;
+        Ok(())
+    }
+
+    fn focusable(&self) -> bool {
+        true
+    }
+
+    fn set_focused(&mut self, focused: bool) {
+        self.focused = focused;
+    }
+
+    fn handle_key_event(&mut self, key: KeyEvent) -> Result<Option<Action>> {
+        if key.kind != KeyEventKind::Release {
+            scroll(&mut self.scroll, key, self.page_height);
+        }
+        Ok(None)
+    }
+
+    fn draw(&mut self, frame: &mut Frame, area: Rect) -> Result<()> {
+        let lines = self.lines();
+        let block = focus_block("Settings", self.focused);
+        self.page_height = block.inner(area).height as usize;
+        self.scroll = self
+            .scroll
+            .min(lines.len().saturating_sub(self.page_height));
+        frame.render_widget(
+            Paragraph::new(lines)
+                .block(block)
+                .scroll((self.scroll as u16, 0)),
+            area,
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,264 @@
+use color_eyre::Result;
+use ratatui::{
+    layout::{Constraint, Layout, Rect},
+    style::Style,
+    text::{Line, Span},
+    widgets::Block,
+    Frame,
+};
+
+use super::Component;
+use crate::{
+    action::Action,
+    app::Mode,
+    cli::VERSION_MESSAGE,
+    config::{Config, StatusBarConfig, StatusSegment, Styles},
+    logging,
+    state::AppState,
+};
+
+/// Segments in the order they are dropped when the terminal is too narrow for all of them.
+const DROP_ORDER: [StatusSegment; 7] = [
+    StatusSegment::Version,
+    StatusSegment::Clock,
+    StatusSegment::LogLevel,
+    StatusSegment::PendingKeys,
+    StatusSegment::Count,
+    StatusSegment::Error,
+    StatusSegment::Mode,
+];
+
+#[derive(Debug, Clone, PartialEq)]
+struct Segment {
+    kind: StatusSegment,
+    text: String,
+    style: Style,
+}
+
+impl Segment {
+    fn span(&self) -> Span<'static> {
+        Span::styled(format!(" {} ", self.text), self.style)
+    }
+
+    fn width(&self) -> usize {
+        self.span().width()
+    }
+}
+
+/// One line at the bottom of the screen with the segments configured in
+/// [`StatusBarConfig`], styled with the `status_bar` and `status_bar.<segment>` styles.
+///
+/// If the segments do not fit, the least important ones are left out, see [`DROP_ORDER`], until
+/// one is left, which is cut off if it is still too wide.
+#[derive(Debug, Clone, Default)]
+pub struct StatusBar {
+    state: Option<AppState>,
+    segments: StatusBarConfig,
+    styles: Styles,
+    last_error: Option<String>,
+    /// The clock as of the last draw, to redraw once it changes.
+    drawn_clock: String,
+}
+
+impl StatusBar {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn style(&self, mode: Mode, key: &str) -> Style {
+        self.styles.get_style(mode, key).unwrap_or_default()
+    }
+
+    fn segment(&self, kind: StatusSegment, state: &AppState) -> Result<Option<Segment>> {
+        let mode = state.snapshot()?.mode;
+        let (key, text) = match kind {
+            StatusSegment::Mode => ("mode", Some(format!("{mode:?}"))),
+            StatusSegment::PendingKeys => (
+                "pending_keys",
+                Some(state.get_pending_keys()?).filter(|keys| !keys.is_empty()),
+            ),
+            StatusSegment::Count => (
+                "count",
+                Some(format!(
+                    "count {} by {}",
+                    state.get_count()?,
+                    state.get_amount()?
+                )),
+            ),
+            StatusSegment::Error => (
+                "error",
+                self.last_error
+                    .as_ref()
+                    .map(|error| error.lines().next().unwrap_or_default().to_string()),
+            ),
+            StatusSegment::LogLevel => (
+                "log_level",
+                logging::level().map(|level| format!("log {level}")),
+            ),
+            StatusSegment::Clock => ("clock", Some(clock())),
+            StatusSegment::Version => ("version", Some(VERSION_MESSAGE.to_string())),
+        };
+        let style = self
+            .style(mode, "status_bar")
+            .patch(self.style(mode, &format!("status_bar.{key}")));
+        Ok(text.map(|text| Segment { kind, text, style }))
+    }
+}
+
+impl Component for StatusBar {
+    fn register_state_handler(&mut self, state: AppState) -> Result<()> {
+        self.state = Some(state);
+        Ok(())
+    }
+
+    fn register_config_handler(&mut self, config: Config) -> Result<()> {
+        self.segments = config.config.status_bar;
+        self.styles = config.styles;
+        Ok(())
+    }
+
+    fn update(&mut self, action: Action) -> Result<Option<Action>> {
+        match action {
+            Action::Error(error) => self.last_error = Some(error),
+            Action::Tick if clock() != self.drawn_clock => return Ok(Some(Action::Render)),
+            _ => {}
+        }
+        Ok(None)
+    }
+
+    fn draw(&mut self, frame: &mut Frame, area: Rect) -> Result<()> {
+        let Some(state) = &self.state else {
+            return Ok(());
+        };
+        self.drawn_clock = clock();
+        let mode = state.snapshot()?.mode;
+        let mut groups = [Vec::new(), Vec::new(), Vec::new()];
+        let configured = [
+            &self.segments.left,
+            &self.segments.center,
+            &self.segments.right,
+        ];
+        for (group, kinds) in groups.iter_mut().zip(configured) {
+            for kind in kinds {
+                group.extend(self.segment(*kind, state)?);
+            }
+        }
+        let [left, center, right] = fit(groups, area.width as usize);
+
+        let line = |segments: &[Segment]| {
+            Line::from(segments.iter().map(Segment::span).collect::<Vec<_>>())
+        };
+        let width =
+            |segments: &[Segment]| segments.iter().map(Segment::width).sum::<usize>() as u16;
+        let [left_area, center_area, right_area] = Layout::horizontal([
+            Constraint::Length(width(&left)),
+            Constraint::Fill(1),
+            Constraint::Length(width(&right)),
+        ])
+        .areas(area);
+        frame.render_widget(Block::new().style(self.style(mode, "status_bar")), area);
+        frame.render_widget(line(&left), left_area);
+        frame.render_widget(line(&center).centered(), center_area);
+        frame.render_widget(line(&right), right_area);
+        Ok(())
+    }
+}
+
+/// Leaves out segments in [`DROP_ORDER`] until the rest fit into `width` columns or one is left,
+/// and cuts off the text of that one if it is still too wide.
+fn fit(mut groups: [Vec<Segment>; 3], width: usize) -> [Vec<Segment>; 3] {
+    let total =
+        |groups: &[Vec<Segment>; 3]| groups.iter().flatten().map(Segment::width).sum::<usize>();
+    for kind in DROP_ORDER {
+        if total(&groups) <= width || groups.iter().flatten().count() <= 1 {
+            break;
+        }
+        for group in groups.iter_mut() {
+            group.retain(|segment| segment.kind != kind);
+        }
+    }
+    if total(&groups) > width {
+        // Since every kind is in `DROP_ORDER`, only one segment is left at this point.
+        if let Some(segment) = groups.iter_mut().flatten().next() {
+            // Two columns of padding and one for the ellipsis.
+            let keep = width.saturating_sub(3);
+            segment.text = segment.text.chars().take(keep).collect::<String>() + "…";
+        }
+    }
+    groups
+}
+
+/// The local time as `HH:MM`, in the system time zone, or UTC if it cannot be determined.
+fn clock() -> String {
+    jiff::Zoned::now().strftime("%H:%M").to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use ratatui::{backend::TestBackend, Terminal};
+
+    use super::*;
+
+    fn segment(kind: StatusSegment, text: &str) -> Segment {
+        Segment {
+            kind,
+            text: text.into(),
+            style: Style::default(),
+        }
+    }
+
+    fn texts(groups: &[Vec<Segment>; 3]) -> Vec<Vec<&str>> {
+        groups
+            .iter()
+            .map(|group| group.iter().map(|segment| segment.text.as_str()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_fit() {
+        let groups = [
+            vec![segment(StatusSegment::Mode, "Home")],
+            vec![segment(StatusSegment::Count, "count 3 by 1")],
+            vec![
+                segment(StatusSegment::Clock, "12:00"),
+                segment(StatusSegment::Version, "0.1.0"),
+            ],
+        ];
+        // " Home " + " count 3 by 1 " + " 12:00 " + " 0.1.0 "
+        assert_eq!(texts(&fit(groups.clone(), 34)).concat().len(), 4);
+        assert_eq!(
+            texts(&fit(groups.clone(), 33)),
+            [vec!["Home"], vec!["count 3 by 1"], vec!["12:00"]]
+        );
+        assert_eq!(
+            texts(&fit(groups.clone(), 10)),
+            [vec!["Home"], vec![], vec![]]
+        );
+        assert_eq!(texts(&fit(groups, 5)), [vec!["Ho…"], vec![], vec![]]);
+    }
+
+    #[test]
+    fn test_draw() {
+        let state = AppState::new();
+        state.increment_count_by(3).unwrap();
+        let mut status_bar = StatusBar::new();
+        status_bar.register_state_handler(state).unwrap();
+        status_bar.segments = StatusBarConfig {
+            left: vec![StatusSegment::Mode, StatusSegment::PendingKeys],
+            center: vec![StatusSegment::Error],
+            right: vec![StatusSegment::Count],
+        };
+        status_bar
+            .update(Action::Error("boom\nmore".into()))
+            .unwrap();
+
+        let mut terminal = Terminal::new(TestBackend::new(40, 1)).unwrap();
+        terminal
+            .draw(|frame| status_bar.draw(frame, frame.area()).unwrap())
+            .unwrap();
+        terminal
+            .backend()
+            .assert_buffer_lines([" Home         boom         count 3 by 1 "]);
+    }
+}
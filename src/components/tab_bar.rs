@@ -0,0 +1,132 @@
+use color_eyre::Result;
+use crossterm::event::{MouseButton, MouseEvent, MouseEventKind};
+use ratatui::{layout::Rect, style::Style, text::Line, widgets::Tabs, Frame};
+
+use super::Component;
+use crate::{
+    action::Action,
+    app::Mode,
+    config::{Config, Styles},
+    state::AppState,
+};
+
+/// Columns around each title, see [`Tabs::padding`].
+const PADDING: u16 = 1;
+/// Columns between two titles, see [`Tabs::divider`].
+const DIVIDER: u16 = 1;
+
+/// One line at the top of the screen with a title per [`Mode::TABS`], styled with the `tab_bar`
+/// and `tab_bar.selected` styles. Clicking a title selects its tab.
+#[derive(Debug, Clone, Default)]
+pub struct TabBar {
+    state: Option<AppState>,
+    styles: Styles,
+    /// The selected tab, which stays highlighted while a popup is open.
+    tab: Mode,
+}
+
+impl TabBar {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn style(&self, key: &str) -> Style {
+        self.styles.get_style(self.tab, key).unwrap_or_default()
+    }
+}
+
+/// The tab whose title, including its padding, is `column` columns from the left of the bar.
+fn tab_at(column: u16) -> Option<Mode> {
+    let mut start = 0;
+    for tab in Mode::TABS {
+        let end = start + PADDING + Line::from(tab.title()).width() as u16 + PADDING;
+        if (start..end).contains(&column) {
+            return Some(tab);
+        }
+        start = end + DIVIDER;
+    }
+    None
+}
+
+impl Component for TabBar {
+    fn register_state_handler(&mut self, state: AppState) -> Result<()> {
+        self.state = Some(state);
+        Ok(())
+    }
+
+    fn register_config_handler(&mut self, config: Config) -> Result<()> {
+        self.styles = config.styles;
+        Ok(())
+    }
+
+    fn handle_mouse_event(&mut self, mouse: MouseEvent, area: Rect) -> Result<Option<Action>> {
+        if mouse.kind != MouseEventKind::Down(MouseButton::Left) || mouse.row != area.y {
+            return Ok(None);
+        }
+        Ok(tab_at(mouse.column.saturating_sub(area.x)).map(Action::SelectTab))
+    }
+
+    fn draw(&mut self, frame: &mut Frame, area: Rect) -> Result<()> {
+        if let Some(state) = &self.state {
+            let mode = state.snapshot()?.mode;
+            if !mode.is_overlay() {
+                self.tab = mode;
+            }
+        }
+        let selected = Mode::TABS.iter().position(|tab| *tab == self.tab);
+        let tabs = Tabs::new(Mode::TABS.map(Mode::title))
+            .select(selected)
+            .style(self.style("tab_bar"))
+            .highlight_style(self.style("tab_bar.selected"));
+        frame.render_widget(tabs, area);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crossterm::event::KeyModifiers;
+    use pretty_assertions::assert_eq;
+    use ratatui::{backend::TestBackend, Terminal};
+
+    use super::*;
+
+    fn click(column: u16, row: u16) -> MouseEvent {
+        MouseEvent {
+            kind: MouseEventKind::Down(MouseButton::Left),
+            column,
+            row,
+            modifiers: KeyModifiers::empty(),
+        }
+    }
+
+    #[test]
+    fn test_click_selects_tab() -> Result<()> {
+        let state = AppState::new();
+        state.set_mode(Mode::Logs)?;
+        let mut tab_bar = TabBar::new();
+        tab_bar.register_state_handler(state)?;
+
+        let mut terminal = Terminal::new(TestBackend::new(40, 1))?;
+        terminal.draw(|frame| tab_bar.draw(frame, frame.area()).unwrap())?;
+        terminal
+            .backend()
+            .assert_buffer_lines([" Home │ Counter │ Logs │ Settings       "]);
+
+        let area = Rect::new(2, 5, 40, 1);
+        let expected = [
+            (2, Some(Action::SelectTab(Mode::Home))),
+            (7, Some(Action::SelectTab(Mode::Home))),
+            (8, None),
+            (9, Some(Action::SelectTab(Mode::Counter))),
+            (24, Some(Action::SelectTab(Mode::Logs))),
+            (26, Some(Action::SelectTab(Mode::Settings))),
+            (36, None),
+        ];
+        for (column, action) in expected {
+            assert_eq!(tab_bar.handle_mouse_event(click(column, 5), area)?, action);
+        }
+        assert_eq!(tab_bar.handle_mouse_event(click(2, 6), area)?, None);
+        Ok(())
+    }
+}
@@ -3,12 +3,17 @@
 use std::{collections::HashMap, env, path::PathBuf};
 
 use color_eyre::Result;
-use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use crossterm::event::{
+    KeyCode, KeyEvent, KeyEventKind, KeyEventState, KeyModifiers, MediaKeyCode, ModifierKeyCode,
+};
 use derive_deref::{Deref, DerefMut};
 use directories::ProjectDirs;
 use lazy_static::lazy_static;
 use ratatui::style::{Color, Modifier, Style};
-use serde::{de::Deserializer, Deserialize};
+use serde::{
+    de::{self, Deserializer},
+    Deserialize,
+};
 use tracing::error;
 
 use crate::{action::Action, app::Mode};
@@ -21,6 +26,156 @@ pub struct AppConfig {
     pub data_dir: PathBuf,
     #[serde(default)]
     pub config_dir: PathBuf,
+    /// Ask before quitting while background tasks are running, e.g. for `CountTo`, with the
+    /// confirmation dialog, naming the tasks.
+    #[serde(default)]
+    pub confirm_quit: bool,
+    /// Draw into an inline viewport of this many lines instead of the alternate screen.
+    #[serde(default)]
+    pub inline_height: Option<u16>,
+    #[serde(default)]
+    pub status_bar: StatusBarConfig,
+    #[serde(default)]
+    pub confirm: ConfirmConfig,
+    #[serde(default)]
+    pub counter: CounterConfig,
+}
+
+/// A piece of information shown in the status bar.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Deserialize)]
+pub enum StatusSegment {
+    /// The current [`Mode`].
+    Mode,
+    /// Keys typed so far of a multi-key binding.
+    PendingKeys,
+    /// The counter and the increment amount.
+    Count,
+    /// The message of the last [`Action::Error`].
+    Error,
+    /// The active log filter.
+    LogLevel,
+    /// The local time.
+    Clock,
+    /// The version and build info.
+    Version,
+}
+
+/// Which button of the confirmation dialog is selected when it opens.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+pub enum ConfirmButton {
+    Yes,
+    #[default]
+    No,
+}
+
+/// Actions that ask for confirmation before they are handled, see [`Action::Confirm`].
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct ConfirmConfig {
+    /// The prompt per action name, e.g. `"Quit": "Quit the application?"`. Applies to the action
+    /// whatever its parameters.
+    pub actions: HashMap<String, String>,
+    pub default_button: ConfirmButton,
+}
+
+impl ConfirmConfig {
+    /// The prompt to confirm `action` with, if it is configured to be confirmed. Names are
+    /// matched ignoring case, since keys are lowercased when the config files are read. Quitting
+    /// without asking, resuming and the answers to the dialog itself are never confirmed.
+    pub fn prompt(&self, action: &Action) -> Option<&String> {
+        if let Action::ForceQuit
+        | Action::Resume
+        | Action::Confirm(..)
+        | Action::Confirmed(..)
+        | Action::CancelConfirm = action
+        {
+            return None;
+        }
+        let name = action.to_string();
+        self.actions
+            .iter()
+            .find(|(configured, _)| configured.eq_ignore_ascii_case(&name))
+            .map(|(_, prompt)| prompt)
+    }
+}
+
+/// What happens when the count is moved past one of its bounds.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+pub enum Overflow {
+    /// Stop at the bound.
+    #[default]
+    Clamp,
+    /// Continue from the other bound, e.g. `max + 1` becomes `min`.
+    Wrap,
+}
+
+/// The range the count is kept in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct CounterConfig {
+    pub min: i32,
+    pub max: i32,
+    pub overflow: Overflow,
+}
+
+impl Default for CounterConfig {
+    fn default() -> Self {
+        Self {
+            min: i32::MIN,
+            max: i32::MAX,
+            overflow: Overflow::Clamp,
+        }
+    }
+}
+
+impl CounterConfig {
+    /// Brings a count that was moved to `count` back within the bounds, by clamping or wrapping.
+    pub fn bound(&self, count: i64) -> i32 {
+        let (min, max) = (i64::from(self.min), i64::from(self.max));
+        let count = match self.overflow {
+            Overflow::Clamp => count.clamp(min, max),
+            Overflow::Wrap => min + (count - min).rem_euclid(max - min + 1),
+        };
+        count as i32
+    }
+
+    /// Brings a count that was set to `count` within the bounds. Always clamps, since wrapping
+    /// only makes sense for moves.
+    pub fn clamp(&self, count: i32) -> i32 {
+        count.clamp(self.min, self.max)
+    }
+
+    /// Where `count` lies between the bounds, from 0 at `min` to 1 at `max`.
+    pub fn ratio(&self, count: i32) -> f64 {
+        if self.min == self.max {
+            return 1.0;
+        }
+        let offset = i64::from(self.clamp(count)) - i64::from(self.min);
+        offset as f64 / (i64::from(self.max) - i64::from(self.min)) as f64
+    }
+}
+
+/// Which segments the status bar shows, and where.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct StatusBarConfig {
+    pub left: Vec<StatusSegment>,
+    pub center: Vec<StatusSegment>,
+    pub right: Vec<StatusSegment>,
+}
+
+impl Default for StatusBarConfig {
+    fn default() -> Self {
+        Self {
+            left: vec![StatusSegment::Mode, StatusSegment::PendingKeys],
+            center: vec![StatusSegment::Count, StatusSegment::Error],
+            right: vec![
+                StatusSegment::LogLevel,
+                StatusSegment::Clock,
+                StatusSegment::Version,
+            ],
+        }
+    }
 }
 
 #[derive(Clone, Debug, Default, Deserialize)]
@@ -76,6 +231,13 @@ impl Config {
         }
 
         let mut cfg: Self = builder.build()?.try_deserialize()?;
+        let counter = &cfg.config.counter;
+        if counter.min > counter.max {
+            return Err(config::ConfigError::Message(format!(
+                "counter.min ({}) is greater than counter.max ({})",
+                counter.min, counter.max
+            )));
+        }
 
         for (mode, default_bindings) in default_config.keybindings.iter() {
             let user_bindings = cfg.keybindings.entry(*mode).or_default();
@@ -132,195 +294,287 @@ impl<'de> Deserialize<'de> for KeyBindings {
     {
         let parsed_map = HashMap::<Mode, HashMap<String, Action>>::deserialize(deserializer)?;
 
-        let keybindings = parsed_map
-            .into_iter()
-            .map(|(mode, inner_map)| {
-                let converted_inner_map = inner_map
-                    .into_iter()
-                    .map(|(key_str, cmd)| (parse_key_sequence(&key_str).unwrap(), cmd))
-                    .collect();
-                (mode, converted_inner_map)
-            })
-            .collect();
+        let mut keybindings = HashMap::new();
+        for (mode, inner_map) in parsed_map {
+            let mut converted_inner_map = HashMap::new();
+            for (key_str, cmd) in inner_map {
+                let keys = parse_key_sequence(&key_str).map_err(de::Error::custom)?;
+                converted_inner_map.insert(keys, cmd);
+            }
+            keybindings.insert(mode, converted_inner_map);
+        }
 
         Ok(KeyBindings(keybindings))
     }
 }
 
+/// Named keys that are not modifiers, in the canonical spelling used by [`key_event_to_string`].
+///
+/// Parsing is case-insensitive for these names; single characters are kept as written.
+const NAMED_KEYS: &[(&str, KeyCode)] = &[
+    ("esc", KeyCode::Esc),
+    ("enter", KeyCode::Enter),
+    ("left", KeyCode::Left),
+    ("right", KeyCode::Right),
+    ("up", KeyCode::Up),
+    ("down", KeyCode::Down),
+    ("home", KeyCode::Home),
+    ("end", KeyCode::End),
+    ("pageup", KeyCode::PageUp),
+    ("pagedown", KeyCode::PageDown),
+    ("backtab", KeyCode::BackTab),
+    ("backspace", KeyCode::Backspace),
+    ("delete", KeyCode::Delete),
+    ("insert", KeyCode::Insert),
+    ("tab", KeyCode::Tab),
+    ("null", KeyCode::Null),
+    ("capslock", KeyCode::CapsLock),
+    ("scrolllock", KeyCode::ScrollLock),
+    ("numlock", KeyCode::NumLock),
+    ("printscreen", KeyCode::PrintScreen),
+    ("pause", KeyCode::Pause),
+    ("menu", KeyCode::Menu),
+    ("keypadbegin", KeyCode::KeypadBegin),
+    ("space", KeyCode::Char(' ')),
+    ("minus", KeyCode::Char('-')),
+    ("hyphen", KeyCode::Char('-')),
+    ("lt", KeyCode::Char('<')),
+    ("gt", KeyCode::Char('>')),
+    ("media-play", KeyCode::Media(MediaKeyCode::Play)),
+    ("media-pause", KeyCode::Media(MediaKeyCode::Pause)),
+    ("media-playpause", KeyCode::Media(MediaKeyCode::PlayPause)),
+    ("media-reverse", KeyCode::Media(MediaKeyCode::Reverse)),
+    ("media-stop", KeyCode::Media(MediaKeyCode::Stop)),
+    (
+        "media-fastforward",
+        KeyCode::Media(MediaKeyCode::FastForward),
+    ),
+    ("media-rewind", KeyCode::Media(MediaKeyCode::Rewind)),
+    ("media-tracknext", KeyCode::Media(MediaKeyCode::TrackNext)),
+    (
+        "media-trackprevious",
+        KeyCode::Media(MediaKeyCode::TrackPrevious),
+    ),
+    ("media-record", KeyCode::Media(MediaKeyCode::Record)),
+    (
+        "media-lowervolume",
+        KeyCode::Media(MediaKeyCode::LowerVolume),
+    ),
+    (
+        "media-raisevolume",
+        KeyCode::Media(MediaKeyCode::RaiseVolume),
+    ),
+    ("media-mutevolume", KeyCode::Media(MediaKeyCode::MuteVolume)),
+    ("leftshift", KeyCode::Modifier(ModifierKeyCode::LeftShift)),
+    ("leftctrl", KeyCode::Modifier(ModifierKeyCode::LeftControl)),
+    ("leftalt", KeyCode::Modifier(ModifierKeyCode::LeftAlt)),
+    ("leftsuper", KeyCode::Modifier(ModifierKeyCode::LeftSuper)),
+    ("lefthyper", KeyCode::Modifier(ModifierKeyCode::LeftHyper)),
+    ("leftmeta", KeyCode::Modifier(ModifierKeyCode::LeftMeta)),
+    ("rightshift", KeyCode::Modifier(ModifierKeyCode::RightShift)),
+    (
+        "rightctrl",
+        KeyCode::Modifier(ModifierKeyCode::RightControl),
+    ),
+    ("rightalt", KeyCode::Modifier(ModifierKeyCode::RightAlt)),
+    ("rightsuper", KeyCode::Modifier(ModifierKeyCode::RightSuper)),
+    ("righthyper", KeyCode::Modifier(ModifierKeyCode::RightHyper)),
+    ("rightmeta", KeyCode::Modifier(ModifierKeyCode::RightMeta)),
+    (
+        "isolevel3shift",
+        KeyCode::Modifier(ModifierKeyCode::IsoLevel3Shift),
+    ),
+    (
+        "isolevel5shift",
+        KeyCode::Modifier(ModifierKeyCode::IsoLevel5Shift),
+    ),
+];
+
+/// Modifier prefixes in the order [`key_event_to_string`] emits them.
+const MODIFIER_PREFIXES: &[(&str, KeyModifiers)] = &[
+    ("ctrl-", KeyModifiers::CONTROL),
+    ("shift-", KeyModifiers::SHIFT),
+    ("alt-", KeyModifiers::ALT),
+    ("super-", KeyModifiers::SUPER),
+    ("hyper-", KeyModifiers::HYPER),
+    ("meta-", KeyModifiers::META),
+];
+
+const KEYPAD_PREFIX: &str = "keypad-";
+
+/// Event kind prefixes; a key without one of these binds to the key press.
+const KIND_PREFIXES: &[(&str, KeyEventKind)] = &[
+    ("repeat-", KeyEventKind::Repeat),
+    ("release-", KeyEventKind::Release),
+];
+
 fn parse_key_event(raw: &str) -> Result<KeyEvent, String> {
-    let raw_lower = raw.to_ascii_lowercase();
-    let (remaining, modifiers) = extract_modifiers(&raw_lower);
-    parse_key_code_with_modifiers(remaining, modifiers)
+    let (remaining, modifiers, kind, state) = extract_modifiers(raw);
+    parse_key_code_with_modifiers(remaining, modifiers, kind, state)
 }
 
-fn extract_modifiers(raw: &str) -> (&str, KeyModifiers) {
+fn extract_modifiers(raw: &str) -> (&str, KeyModifiers, KeyEventKind, KeyEventState) {
     let mut modifiers = KeyModifiers::empty();
+    let mut kind = KeyEventKind::Press;
+    let mut state = KeyEventState::empty();
     let mut current = raw;
 
-    loop {
-        match current {
-            rest if rest.starts_with("ctrl-") => {
-                modifiers.insert(KeyModifiers::CONTROL);
-                current = &rest[5..];
-            }
-            rest if rest.starts_with("alt-") => {
-                modifiers.insert(KeyModifiers::ALT);
-                current = &rest[4..];
+    'outer: loop {
+        // A lone trailing character such as the `-` in `ctrl--` is a key, not a prefix.
+        if current.chars().count() <= 1 {
+            break;
+        }
+        if strip_prefix_ignore_case(current, KEYPAD_PREFIX).is_some() {
+            state.insert(KeyEventState::KEYPAD);
+            current = &current[KEYPAD_PREFIX.len()..];
+            continue;
+        }
+        for (prefix, modifier) in MODIFIER_PREFIXES {
+            if let Some(rest) = strip_prefix_ignore_case(current, prefix) {
+                modifiers.insert(*modifier);
+                current = rest;
+                continue 'outer;
             }
-            rest if rest.starts_with("shift-") => {
-                modifiers.insert(KeyModifiers::SHIFT);
-                current = &rest[6..];
+        }
+        for (prefix, event_kind) in KIND_PREFIXES {
+            if let Some(rest) = strip_prefix_ignore_case(current, prefix) {
+                kind = *event_kind;
+                current = rest;
+                continue 'outer;
             }
-            _ => break, // break out of the loop if no known prefix is detected
-        };
+        }
+        break; // break out of the loop if no known prefix is detected
     }
 
-    (current, modifiers)
+    (current, modifiers, kind, state)
+}
+
+fn strip_prefix_ignore_case<'a>(raw: &'a str, prefix: &str) -> Option<&'a str> {
+    let head = raw.get(..prefix.len())?;
+    head.eq_ignore_ascii_case(prefix)
+        .then(|| &raw[prefix.len()..])
 }
 
 fn parse_key_code_with_modifiers(
     raw: &str,
     mut modifiers: KeyModifiers,
+    kind: KeyEventKind,
+    state: KeyEventState,
 ) -> Result<KeyEvent, String> {
-    let c = match raw {
-        "esc" => KeyCode::Esc,
-        "enter" => KeyCode::Enter,
-        "left" => KeyCode::Left,
-        "right" => KeyCode::Right,
-        "up" => KeyCode::Up,
-        "down" => KeyCode::Down,
-        "home" => KeyCode::Home,
-        "end" => KeyCode::End,
-        "pageup" => KeyCode::PageUp,
-        "pagedown" => KeyCode::PageDown,
-        "backtab" => {
-            modifiers.insert(KeyModifiers::SHIFT);
-            KeyCode::BackTab
+    let mut chars = raw.chars();
+    let c = match (chars.next(), chars.next()) {
+        (Some(c), None) => {
+            let c = if modifiers.contains(KeyModifiers::SHIFT) {
+                c.to_ascii_uppercase()
+            } else {
+                c
+            };
+            KeyCode::Char(c)
         }
-        "backspace" => KeyCode::Backspace,
-        "delete" => KeyCode::Delete,
-        "insert" => KeyCode::Insert,
-        "f1" => KeyCode::F(1),
-        "f2" => KeyCode::F(2),
-        "f3" => KeyCode::F(3),
-        "f4" => KeyCode::F(4),
-        "f5" => KeyCode::F(5),
-        "f6" => KeyCode::F(6),
-        "f7" => KeyCode::F(7),
-        "f8" => KeyCode::F(8),
-        "f9" => KeyCode::F(9),
-        "f10" => KeyCode::F(10),
-        "f11" => KeyCode::F(11),
-        "f12" => KeyCode::F(12),
-        "space" => KeyCode::Char(' '),
-        "hyphen" => KeyCode::Char('-'),
-        "minus" => KeyCode::Char('-'),
-        "tab" => KeyCode::Tab,
-        c if c.len() == 1 => {
-            let mut c = c.chars().next().unwrap();
-            if modifiers.contains(KeyModifiers::SHIFT) {
-                c = c.to_ascii_uppercase();
+        _ => {
+            let lower = raw.to_ascii_lowercase();
+            if let Some((_, code)) = NAMED_KEYS.iter().find(|(name, _)| *name == lower) {
+                *code
+            } else if let Some(n) = lower.strip_prefix('f').and_then(|n| n.parse::<u8>().ok()) {
+                KeyCode::F(n)
+            } else {
+                return Err(format!("Unable to parse {raw}"));
             }
-            KeyCode::Char(c)
         }
-        _ => return Err(format!("Unable to parse {raw}")),
     };
-    Ok(KeyEvent::new(c, modifiers))
+    if c == KeyCode::BackTab {
+        modifiers.insert(KeyModifiers::SHIFT);
+    }
+    Ok(KeyEvent::new_with_kind_and_state(c, modifiers, kind, state))
 }
 
+/// Formats a single key event in the notation accepted by [`parse_key_event`], e.g. `ctrl-alt-a`.
 pub fn key_event_to_string(key_event: &KeyEvent) -> String {
-    let char;
     let key_code = match key_event.code {
-        KeyCode::Backspace => "backspace",
-        KeyCode::Enter => "enter",
-        KeyCode::Left => "left",
-        KeyCode::Right => "right",
-        KeyCode::Up => "up",
-        KeyCode::Down => "down",
-        KeyCode::Home => "home",
-        KeyCode::End => "end",
-        KeyCode::PageUp => "pageup",
-        KeyCode::PageDown => "pagedown",
-        KeyCode::Tab => "tab",
-        KeyCode::BackTab => "backtab",
-        KeyCode::Delete => "delete",
-        KeyCode::Insert => "insert",
-        KeyCode::F(c) => {
-            char = format!("f({c})");
-            &char
-        }
-        KeyCode::Char(' ') => "space",
-        KeyCode::Char(c) => {
-            char = c.to_string();
-            &char
-        }
-        KeyCode::Esc => "esc",
-        KeyCode::Null => "",
-        KeyCode::CapsLock => "",
-        KeyCode::Menu => "",
-        KeyCode::ScrollLock => "",
-        KeyCode::Media(_) => "",
-        KeyCode::NumLock => "",
-        KeyCode::PrintScreen => "",
-        KeyCode::Pause => "",
-        KeyCode::KeypadBegin => "",
-        KeyCode::Modifier(_) => "",
+        KeyCode::F(n) => format!("f{n}"),
+        KeyCode::Char(c) if !matches!(c, ' ' | '-' | '<' | '>') => c.to_string(),
+        code => NAMED_KEYS
+            .iter()
+            .find(|(_, named)| *named == code)
+            .map(|(name, _)| name.to_string())
+            .unwrap_or_default(),
     };
 
-    let mut modifiers = Vec::with_capacity(3);
+    let mut key = String::new();
 
-    if key_event.modifiers.intersects(KeyModifiers::CONTROL) {
-        modifiers.push("ctrl");
+    for (prefix, event_kind) in KIND_PREFIXES {
+        if key_event.kind == *event_kind {
+            key.push_str(prefix);
+        }
     }
 
-    if key_event.modifiers.intersects(KeyModifiers::SHIFT) {
-        modifiers.push("shift");
+    for (prefix, modifier) in MODIFIER_PREFIXES {
+        if key_event.modifiers.intersects(*modifier) {
+            key.push_str(prefix);
+        }
     }
 
-    if key_event.modifiers.intersects(KeyModifiers::ALT) {
-        modifiers.push("alt");
+    if key_event.state.contains(KeyEventState::KEYPAD) {
+        key.push_str(KEYPAD_PREFIX);
     }
 
-    let mut key = modifiers.join("-");
-
-    if !key.is_empty() {
-        key.push('-');
-    }
-    key.push_str(key_code);
+    key.push_str(&key_code);
 
     key
 }
 
+/// Formats a key sequence as `<key><key>...`, the notation accepted by [`parse_key_sequence`].
+pub fn key_sequence_to_string(key_events: &[KeyEvent]) -> String {
+    key_events
+        .iter()
+        .map(|key_event| format!("<{}>", key_event_to_string(key_event)))
+        .collect()
+}
+
+/// Parses a key sequence such as `<ctrl-x><ctrl-s>`.
+///
+/// A single key may also be written without brackets, e.g. `q`.
 pub fn parse_key_sequence(raw: &str) -> Result<Vec<KeyEvent>, String> {
-    if raw.chars().filter(|c| *c == '>').count() != raw.chars().filter(|c| *c == '<').count() {
-        return Err(format!("Unable to parse `{}`", raw));
-    }
-    let raw = if !raw.contains("><") {
-        let raw = raw.strip_prefix('<').unwrap_or(raw);
-        let raw = raw.strip_prefix('>').unwrap_or(raw);
-        raw
-    } else {
-        raw
+    let Some(mut rest) = raw.strip_prefix('<') else {
+        if raw.is_empty() || raw.contains(['<', '>']) {
+            return Err(format!("Unable to parse `{}`", raw));
+        }
+        return parse_key_event(raw).map(|key_event| vec![key_event]);
     };
-    let sequences = raw
-        .split("><")
-        .map(|seq| {
-            if let Some(s) = seq.strip_prefix('<') {
-                s
-            } else if let Some(s) = seq.strip_suffix('>') {
-                s
-            } else {
-                seq
-            }
-        })
-        .collect::<Vec<_>>();
 
-    sequences.into_iter().map(parse_key_event).collect()
+    let mut sequence = Vec::new();
+    loop {
+        let Some((key, remaining)) = rest.split_once('>') else {
+            return Err(format!("Unable to parse `{}`", raw));
+        };
+        if key.is_empty() || key.contains('<') {
+            return Err(format!("Unable to parse `{}`", raw));
+        }
+        sequence.push(parse_key_event(key)?);
+        if remaining.is_empty() {
+            break;
+        }
+        let Some(remaining) = remaining.strip_prefix('<') else {
+            return Err(format!("Unable to parse `{}`", raw));
+        };
+        rest = remaining;
+    }
+
+    Ok(sequence)
 }
 
 #[derive(Clone, Debug, Default, Deref, DerefMut)]
 pub struct Styles(pub HashMap<Mode, HashMap<String, Style>>);
 
+impl Styles {
+    /// The style called `key` in `mode`, falling back to the one in [`Mode::Home`].
+    pub fn get_style(&self, mode: Mode, key: &str) -> Option<Style> {
+        [mode, Mode::Home]
+            .iter()
+            .find_map(|mode| self.get(mode)?.get(key).copied())
+    }
+}
+
 impl<'de> Deserialize<'de> for Styles {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
@@ -450,6 +704,7 @@ fn parse_color(s: &str) -> Option<Color> {
 #[cfg(test)]
 mod tests {
     use pretty_assertions::assert_eq;
+    use proptest::prelude::*;
 
     use super::*;
 
@@ -514,6 +769,53 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_confirm_prompt() {
+        let confirm = ConfirmConfig {
+            actions: HashMap::from([
+                ("incrementby".to_string(), "Sure?".to_string()),
+                ("forcequit".to_string(), "Sure?".to_string()),
+                ("confirmed".to_string(), "Sure?".to_string()),
+            ]),
+            default_button: ConfirmButton::No,
+        };
+        assert_eq!(
+            confirm.prompt(&Action::IncrementBy(5)).map(String::as_str),
+            Some("Sure?")
+        );
+        assert_eq!(confirm.prompt(&Action::DecrementBy(5)), None);
+        assert_eq!(confirm.prompt(&Action::ForceQuit), None);
+        let confirmed = Action::Confirmed(Box::new(Action::IncrementBy(5)));
+        assert_eq!(confirm.prompt(&confirmed), None);
+    }
+
+    #[test]
+    fn test_counter_bounds() {
+        let mut counter = CounterConfig {
+            min: -10,
+            max: 10,
+            overflow: Overflow::Clamp,
+        };
+        assert_eq!(counter.bound(5), 5);
+        assert_eq!(counter.bound(11), 10);
+        assert_eq!(counter.bound(-25), -10);
+        assert_eq!(counter.ratio(0), 0.5);
+        assert_eq!(counter.ratio(100), 1.0);
+
+        counter.overflow = Overflow::Wrap;
+        assert_eq!(counter.bound(10), 10);
+        assert_eq!(counter.bound(11), -10);
+        assert_eq!(counter.bound(-12), 9);
+        assert_eq!(counter.bound(50), 8);
+        assert_eq!(counter.clamp(50), 10);
+
+        let unbounded = CounterConfig {
+            overflow: Overflow::Wrap,
+            ..CounterConfig::default()
+        };
+        assert_eq!(unbounded.bound(i64::from(i32::MAX) + 1), i32::MIN);
+    }
+
     #[test]
     fn test_simple_keys() {
         assert_eq!(
@@ -595,4 +897,203 @@ mod tests {
             KeyEvent::new(KeyCode::Enter, KeyModifiers::ALT)
         );
     }
+
+    #[test]
+    fn test_function_keys() {
+        assert_eq!(
+            parse_key_event("f12").unwrap(),
+            KeyEvent::new(KeyCode::F(12), KeyModifiers::empty())
+        );
+        assert_eq!(
+            key_event_to_string(&KeyEvent::new(KeyCode::F(3), KeyModifiers::empty())),
+            "f3".to_string()
+        );
+    }
+
+    #[test]
+    fn test_extended_modifiers_and_keypad() {
+        assert_eq!(
+            parse_key_event("super-hyper-meta-a").unwrap(),
+            KeyEvent::new(
+                KeyCode::Char('a'),
+                KeyModifiers::SUPER | KeyModifiers::HYPER | KeyModifiers::META
+            )
+        );
+        assert_eq!(
+            parse_key_event("ctrl-keypad-5").unwrap(),
+            KeyEvent::new_with_kind_and_state(
+                KeyCode::Char('5'),
+                KeyModifiers::CONTROL,
+                KeyEventKind::Press,
+                KeyEventState::KEYPAD
+            )
+        );
+        assert_eq!(
+            parse_key_event("media-playpause").unwrap(),
+            KeyEvent::new(
+                KeyCode::Media(MediaKeyCode::PlayPause),
+                KeyModifiers::empty()
+            )
+        );
+    }
+
+    #[test]
+    fn test_key_event_kinds() {
+        assert_eq!(
+            parse_key_event("release-space").unwrap(),
+            KeyEvent::new_with_kind(
+                KeyCode::Char(' '),
+                KeyModifiers::empty(),
+                KeyEventKind::Release
+            )
+        );
+        assert_eq!(
+            key_event_to_string(&KeyEvent::new_with_kind(
+                KeyCode::Right,
+                KeyModifiers::CONTROL,
+                KeyEventKind::Repeat
+            )),
+            "repeat-ctrl-right".to_string()
+        );
+    }
+
+    #[test]
+    fn test_key_sequence() {
+        assert_eq!(
+            parse_key_sequence("<ctrl-x><lt><minus>").unwrap(),
+            vec![
+                KeyEvent::new(KeyCode::Char('x'), KeyModifiers::CONTROL),
+                KeyEvent::new(KeyCode::Char('<'), KeyModifiers::empty()),
+                KeyEvent::new(KeyCode::Char('-'), KeyModifiers::empty()),
+            ]
+        );
+        assert_eq!(
+            parse_key_sequence("q").unwrap(),
+            vec![KeyEvent::new(KeyCode::Char('q'), KeyModifiers::empty())]
+        );
+        assert_eq!(
+            key_sequence_to_string(&[
+                KeyEvent::new(KeyCode::Char('g'), KeyModifiers::empty()),
+                KeyEvent::new(KeyCode::Char(' '), KeyModifiers::SHIFT),
+            ]),
+            "<g><shift-space>".to_string()
+        );
+    }
+
+    #[test]
+    fn test_invalid_key_sequences() {
+        assert!(parse_key_sequence("").is_err());
+        assert!(parse_key_sequence("<>").is_err());
+        assert!(parse_key_sequence("<a").is_err());
+        assert!(parse_key_sequence("<a>b").is_err());
+        assert!(parse_key_sequence("<a<b>>").is_err());
+    }
+
+    /// Every key code crossterm has, listed independently of [`NAMED_KEYS`] so that a key missing
+    /// from the table fails the round trip.
+    fn key_code_strategy() -> impl Strategy<Value = KeyCode> {
+        use MediaKeyCode as Media;
+        use ModifierKeyCode as Modifier;
+        prop_oneof![
+            proptest::sample::select(vec![
+                KeyCode::Backspace,
+                KeyCode::Enter,
+                KeyCode::Left,
+                KeyCode::Right,
+                KeyCode::Up,
+                KeyCode::Down,
+                KeyCode::Home,
+                KeyCode::End,
+                KeyCode::PageUp,
+                KeyCode::PageDown,
+                KeyCode::Tab,
+                KeyCode::BackTab,
+                KeyCode::Delete,
+                KeyCode::Insert,
+                KeyCode::Null,
+                KeyCode::Esc,
+                KeyCode::CapsLock,
+                KeyCode::ScrollLock,
+                KeyCode::NumLock,
+                KeyCode::PrintScreen,
+                KeyCode::Pause,
+                KeyCode::Menu,
+                KeyCode::KeypadBegin,
+            ]),
+            any::<u8>().prop_map(KeyCode::F),
+            any::<char>().prop_map(KeyCode::Char),
+            proptest::sample::select(vec![
+                Media::Play,
+                Media::Pause,
+                Media::PlayPause,
+                Media::Reverse,
+                Media::Stop,
+                Media::FastForward,
+                Media::Rewind,
+                Media::TrackNext,
+                Media::TrackPrevious,
+                Media::Record,
+                Media::LowerVolume,
+                Media::RaiseVolume,
+                Media::MuteVolume,
+            ])
+            .prop_map(KeyCode::Media),
+            proptest::sample::select(vec![
+                Modifier::LeftShift,
+                Modifier::LeftControl,
+                Modifier::LeftAlt,
+                Modifier::LeftSuper,
+                Modifier::LeftHyper,
+                Modifier::LeftMeta,
+                Modifier::RightShift,
+                Modifier::RightControl,
+                Modifier::RightAlt,
+                Modifier::RightSuper,
+                Modifier::RightHyper,
+                Modifier::RightMeta,
+                Modifier::IsoLevel3Shift,
+                Modifier::IsoLevel5Shift,
+            ])
+            .prop_map(KeyCode::Modifier),
+        ]
+    }
+
+    fn key_event_strategy() -> impl Strategy<Value = KeyEvent> {
+        (
+            key_code_strategy(),
+            any::<u8>().prop_map(KeyModifiers::from_bits_truncate),
+            proptest::sample::select(vec![
+                KeyEventKind::Press,
+                KeyEventKind::Repeat,
+                KeyEventKind::Release,
+            ]),
+            any::<bool>(),
+        )
+            .prop_map(|(code, mut modifiers, kind, keypad)| {
+                // crossterm always reports BackTab together with Shift.
+                if code == KeyCode::BackTab {
+                    modifiers.insert(KeyModifiers::SHIFT);
+                }
+                let state = if keypad {
+                    KeyEventState::KEYPAD
+                } else {
+                    KeyEventState::empty()
+                };
+                KeyEvent::new_with_kind_and_state(code, modifiers, kind, state)
+            })
+    }
+
+    proptest! {
+        #[test]
+        fn test_key_event_round_trip(key_event in key_event_strategy()) {
+            let raw = key_event_to_string(&key_event);
+            prop_assert_eq!(parse_key_event(&raw), Ok(key_event));
+        }
+
+        #[test]
+        fn test_key_sequence_round_trip(key_events in proptest::collection::vec(key_event_strategy(), 1..5)) {
+            let raw = key_sequence_to_string(&key_events);
+            prop_assert_eq!(parse_key_sequence(&raw), Ok(key_events));
+        }
+    }
 }
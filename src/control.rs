@@ -0,0 +1,308 @@
+//! Optional Unix domain socket that lets scripts and editor plugins drive a running instance.
+//!
+//! Each line sent by a client is a JSON request and is answered by one JSON line.
+
+#[cfg(unix)]
+use std::os::unix::fs::{DirBuilderExt, FileTypeExt, PermissionsExt};
+use std::path::{Path, PathBuf};
+
+use color_eyre::{eyre::eyre, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+#[cfg(unix)]
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+    net::{UnixListener, UnixStream},
+};
+use tokio::{
+    sync::{mpsc::Sender, oneshot},
+    task::JoinHandle,
+};
+use tracing::{debug, error, info};
+
+use crate::{
+    action::Action,
+    state::{AppState, SharedState},
+};
+
+/// An action received on the control socket.
+///
+/// The app replies on `processed` once the action and everything it triggered have been handled,
+/// so that the snapshot sent back to the client reflects it.
+pub struct ControlRequest {
+    pub action: Action,
+    pub processed: oneshot::Sender<()>,
+}
+
+/// A request line: either an [`Action`] in its JSON encoding, e.g. `{"IncrementBy": 5}`, or a
+/// query such as `{"query": "count"}`.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum Request {
+    Query { query: Query },
+    Action(Action),
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum Query {
+    Count,
+    Amount,
+    Mode,
+    State,
+}
+
+/// A reply line. `state` is the snapshot after the request was handled.
+#[derive(Debug, Default, Serialize)]
+struct Response {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    value: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    state: Option<SharedState>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+/// Binds a Unix domain socket at `path` and serves newline-delimited JSON requests until the
+/// returned task is aborted. A stale socket file from a previous run is replaced, but a socket
+/// another instance listens on or any other kind of file is an error.
+#[cfg(unix)]
+pub fn spawn_control_server(
+    path: PathBuf,
+    state: AppState,
+    control_tx: Sender<ControlRequest>,
+) -> Result<JoinHandle<()>> {
+    remove_stale_socket(&path)?;
+    let listener = bind_private(&path)?;
+    info!("Control socket listening on {}", path.display());
+
+    Ok(tokio::spawn(async move {
+        loop {
+            let stream = match listener.accept().await {
+                Ok((stream, _)) => stream,
+                Err(err) => {
+                    error!("Control socket accept failed: {err}");
+                    break;
+                }
+            };
+            let state = state.clone();
+            let control_tx = control_tx.clone();
+            tokio::spawn(async move {
+                if let Err(err) = serve_client(stream, state, control_tx).await {
+                    debug!("Control client disconnected: {err}");
+                }
+            });
+        }
+    }))
+}
+
+/// Removes the socket at `path` if nothing listens on it any more.
+#[cfg(unix)]
+fn remove_stale_socket(path: &Path) -> Result<()> {
+    let metadata = match std::fs::symlink_metadata(path) {
+        Ok(metadata) => metadata,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(err) => return Err(err.into()),
+    };
+    if !metadata.file_type().is_socket() {
+        return Err(eyre!("{} exists and is not a socket", path.display()));
+    }
+    if std::os::unix::net::UnixStream::connect(path).is_ok() {
+        return Err(eyre!("{} is in use by another instance", path.display()));
+    }
+    remove_socket(path);
+    Ok(())
+}
+
+/// Binds a listener at `path` that only the current user can connect to. The socket is bound in a
+/// private directory, so that it is never reachable with the permissions of the umask, and then
+/// linked into place, which fails rather than replace a file that appeared in the meantime.
+#[cfg(unix)]
+fn bind_private(path: &Path) -> Result<UnixListener> {
+    let parent = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    let private_dir = parent.join(format!(".control-{}", std::process::id()));
+    std::fs::DirBuilder::new()
+        .mode(0o700)
+        .create(&private_dir)?;
+    let private_path = private_dir.join("socket");
+    let bind = || -> Result<UnixListener> {
+        let listener = UnixListener::bind(&private_path)?;
+        std::fs::set_permissions(&private_path, std::fs::Permissions::from_mode(0o600))?;
+        std::fs::hard_link(&private_path, path)?;
+        Ok(listener)
+    };
+    let listener = bind();
+    remove_socket(&private_path);
+    let _ = std::fs::remove_dir(&private_dir);
+    listener
+}
+
+#[cfg(not(unix))]
+pub fn spawn_control_server(
+    _path: PathBuf,
+    _state: AppState,
+    _control_tx: Sender<ControlRequest>,
+) -> Result<JoinHandle<()>> {
+    Err(color_eyre::eyre::eyre!(
+        "The control socket is only supported on Unix"
+    ))
+}
+
+/// Removes the socket file, ignoring a file that is already gone.
+pub fn remove_socket(path: &Path) {
+    let _ = std::fs::remove_file(path);
+}
+
+#[cfg(unix)]
+async fn serve_client(
+    stream: UnixStream,
+    state: AppState,
+    control_tx: Sender<ControlRequest>,
+) -> Result<()> {
+    let (reader, mut writer) = stream.into_split();
+    let mut lines = BufReader::new(reader).lines();
+    while let Some(line) = lines.next_line().await? {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let response = handle_request(&line, &state, &control_tx).await;
+        let mut reply = serde_json::to_string(&response)?;
+        reply.push('\n');
+        writer.write_all(reply.as_bytes()).await?;
+    }
+    Ok(())
+}
+
+async fn handle_request(
+    line: &str,
+    state: &AppState,
+    control_tx: &Sender<ControlRequest>,
+) -> Response {
+    let request = match serde_json::from_str::<Request>(line) {
+        Ok(request) => request,
+        Err(err) => {
+            return Response {
+                error: Some(format!("Invalid request: {err}")),
+                ..Default::default()
+            }
+        }
+    };
+
+    let query = match request {
+        Request::Query { query } => Some(query),
+        Request::Action(action) => {
+            debug!("Control action: {action:?}");
+            let (processed, done) = oneshot::channel();
+            if control_tx
+                .send(ControlRequest { action, processed })
+                .await
+                .is_err()
+                || done.await.is_err()
+            {
+                return Response {
+                    error: Some("App is shutting down".to_string()),
+                    ..Default::default()
+                };
+            }
+            None
+        }
+    };
+
+    match state.snapshot() {
+        Ok(snapshot) => Response {
+            value: query.and_then(|query| match query {
+                Query::Count => Some(snapshot.count.into()),
+                Query::Amount => Some(snapshot.amount.clone().into()),
+                Query::Mode => serde_json::to_value(snapshot.mode).ok(),
+                Query::State => None,
+            }),
+            state: Some(snapshot),
+            error: None,
+        },
+        Err(err) => Response {
+            error: Some(err.to_string()),
+            ..Default::default()
+        },
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use tokio::sync::mpsc;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_round_trip() -> Result<()> {
+        let path = std::env::temp_dir().join(format!("control-test-{}.sock", std::process::id()));
+        let state = AppState::new();
+        let (control_tx, mut control_rx) = mpsc::channel(8);
+        let server = spawn_control_server(path.clone(), state.clone(), control_tx)?;
+
+        // Stand-in for the app loop.
+        let app_state = state.clone();
+        tokio::spawn(async move {
+            while let Some(request) = control_rx.recv().await {
+                if let Action::IncrementBy(amount) = request.action {
+                    app_state.increment_count_by(amount).unwrap();
+                }
+                let _ = request.processed.send(());
+            }
+        });
+
+        let stream = UnixStream::connect(&path).await?;
+        let (reader, mut writer) = stream.into_split();
+        let mut lines = BufReader::new(reader).lines();
+
+        writer.write_all(b"{\"IncrementBy\": 3}\n").await?;
+        let reply: Value = serde_json::from_str(&lines.next_line().await?.unwrap())?;
+        assert_eq!(reply["state"]["count"], 3);
+
+        writer.write_all(b"{\"query\": \"amount\"}\n").await?;
+        let reply: Value = serde_json::from_str(&lines.next_line().await?.unwrap())?;
+        assert_eq!(reply["value"], "1");
+
+        writer.write_all(b"nonsense\n").await?;
+        let reply: Value = serde_json::from_str(&lines.next_line().await?.unwrap())?;
+        assert!(reply["error"].is_string());
+
+        server.abort();
+        remove_socket(&path);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_socket_path_checks() -> Result<()> {
+        let dir = std::env::temp_dir().join(format!("control-paths-{}", std::process::id()));
+        std::fs::create_dir_all(&dir)?;
+        let (control_tx, _control_rx) = mpsc::channel(8);
+        let spawn =
+            |path: &Path| spawn_control_server(path.into(), AppState::new(), control_tx.clone());
+
+        // A regular file is left alone.
+        let file = dir.join("notes.txt");
+        std::fs::write(&file, "keep me")?;
+        assert!(spawn(&file).is_err());
+        assert_eq!(std::fs::read_to_string(&file)?, "keep me");
+
+        // A stale socket is replaced, and the new one is private.
+        let path = dir.join("control.sock");
+        drop(std::os::unix::net::UnixListener::bind(&path)?);
+        let server = spawn(&path)?;
+        let mode = std::fs::metadata(&path)?.permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        UnixStream::connect(&path).await?;
+
+        // A socket in use is not taken over.
+        assert!(spawn(&path).is_err());
+        UnixStream::connect(&path).await?;
+
+        server.abort();
+        std::fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,283 @@
+//! Crash reports with the context needed to reproduce a panic.
+//!
+//! The app registers its state and config here and records every action it handles. When the
+//! panic hook runs, all of it is written to a JSON file in the `crashes` folder of the data
+//! directory, together with the panic report, the terminal size and the build info. The `crashes`
+//! subcommand lists and shows these reports.
+
+use std::{
+    collections::{BTreeMap, VecDeque},
+    io::Write,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use color_eyre::{eyre::eyre, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    action::Action,
+    cli::VERSION_MESSAGE,
+    config::{self, key_sequence_to_string, Config},
+    logging,
+    state::{AppState, SharedState},
+};
+
+/// Number of most recently handled actions kept for a crash report.
+pub const CRASH_ACTION_HISTORY: usize = 50;
+const CRASH_DIR: &str = "crashes";
+const CRASH_FILE_PREFIX: &str = "crash-";
+const CRASH_FILE_EXTENSION: &str = "json";
+
+#[derive(Debug, Default)]
+struct CrashContext {
+    recent_actions: VecDeque<String>,
+    state: Option<AppState>,
+    config: Option<String>,
+}
+
+lazy_static::lazy_static! {
+    static ref CONTEXT: Mutex<CrashContext> = Mutex::new(CrashContext::default());
+}
+
+/// Registers the state and config of the running app, to be included in crash reports.
+pub fn set_context(state: AppState, config: &Config) {
+    if let Ok(mut context) = CONTEXT.lock() {
+        context.state = Some(state);
+        context.config = Some(describe_config(config));
+    }
+}
+
+/// Remembers an action handled by the app; only the last [`CRASH_ACTION_HISTORY`] are kept.
+pub fn record_action(action: &Action) {
+    let Ok(mut context) = CONTEXT.lock() else {
+        return;
+    };
+    if context.recent_actions.len() == CRASH_ACTION_HISTORY {
+        context.recent_actions.pop_front();
+    }
+    context.recent_actions.push_back(action.to_command());
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CrashReport {
+    /// RFC 3339 timestamp in UTC.
+    pub time: String,
+    pub version: String,
+    pub panic: String,
+    /// Oldest first, in command syntax.
+    pub recent_actions: Vec<String>,
+    pub state: Option<SharedState>,
+    pub config: Option<String>,
+    /// Columns and rows.
+    pub terminal_size: Option<(u16, u16)>,
+    pub log_file: Option<PathBuf>,
+}
+
+impl CrashReport {
+    /// Collects the registered context. Never blocks, since the panic may have happened while a
+    /// lock was held.
+    pub fn capture(panic: String) -> Self {
+        let (recent_actions, state, config) = match CONTEXT.try_lock() {
+            Ok(context) => (
+                context.recent_actions.iter().cloned().collect(),
+                context.state.as_ref().and_then(AppState::try_snapshot),
+                context.config.clone(),
+            ),
+            Err(_) => (Vec::new(), None, None),
+        };
+        Self {
+            time: jiff::Timestamp::now().to_string(),
+            version: VERSION_MESSAGE.to_string(),
+            panic,
+            recent_actions,
+            state,
+            config,
+            terminal_size: crossterm::terminal::size().ok(),
+            log_file: logging::log_path(),
+        }
+    }
+
+    /// Writes the report to a new file in the crash directory and returns its path.
+    pub fn write(&self) -> Result<PathBuf> {
+        let directory = crash_dir();
+        std::fs::create_dir_all(&directory)?;
+        let timestamp = jiff::Timestamp::now().strftime("%Y%m%dT%H%M%SZ");
+        let path = directory.join(format!(
+            "{CRASH_FILE_PREFIX}{timestamp}-{}.{CRASH_FILE_EXTENSION}",
+            std::process::id()
+        ));
+        let file = std::fs::File::create(&path)?;
+        serde_json::to_writer_pretty(file, self)?;
+        Ok(path)
+    }
+
+    fn write_text(&self, out: &mut impl Write) -> Result<()> {
+        writeln!(out, "Time: {}", self.time)?;
+        writeln!(out, "Version: {}", self.version)?;
+        if let Some((columns, rows)) = self.terminal_size {
+            writeln!(out, "Terminal size: {columns}x{rows}")?;
+        }
+        if let Some(log_file) = &self.log_file {
+            writeln!(out, "Log file: {}", log_file.display())?;
+        }
+        if let Some(state) = &self.state {
+            writeln!(
+                out,
+                "State: count {}, amount {}, mode {:?}",
+                state.count, state.amount, state.mode
+            )?;
+        }
+        writeln!(out, "\nRecent actions (oldest first):")?;
+        for action in &self.recent_actions {
+            writeln!(out, "  {action}")?;
+        }
+        if let Some(config) = &self.config {
+            writeln!(out, "\nConfig:\n{config}")?;
+        }
+        writeln!(out, "\n{}", self.panic)?;
+        Ok(())
+    }
+}
+
+fn crash_dir() -> PathBuf {
+    config::get_data_dir().join(CRASH_DIR)
+}
+
+/// The app config and keybindings, one binding per line.
+fn describe_config(config: &Config) -> String {
+    let mut description = format!("{:?}\n", config.config);
+    for (mode, bindings) in config.keybindings.iter() {
+        let bindings = bindings
+            .iter()
+            .map(|(keys, action)| (key_sequence_to_string(keys), action.to_command()))
+            .collect::<BTreeMap<_, _>>();
+        for (keys, action) in bindings {
+            description.push_str(&format!("  {mode:?} {keys} {action}\n"));
+        }
+    }
+    description
+}
+
+/// Names of the crash reports in `directory`, oldest first.
+fn report_names(directory: &Path) -> Result<Vec<String>> {
+    if !directory.exists() {
+        return Ok(Vec::new());
+    }
+    let mut names = std::fs::read_dir(directory)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|ext| ext == CRASH_FILE_EXTENSION)
+        })
+        .filter_map(|path| path.file_stem()?.to_str().map(str::to_string))
+        .filter(|name| name.starts_with(CRASH_FILE_PREFIX))
+        .collect::<Vec<_>>();
+    // The timestamp in the name sorts chronologically.
+    names.sort();
+    Ok(names)
+}
+
+fn read_report(directory: &Path, name: &str) -> Result<CrashReport> {
+    let path = directory.join(format!("{name}.{CRASH_FILE_EXTENSION}"));
+    let file = std::fs::File::open(&path)
+        .map_err(|err| eyre!("Unable to open {}: {err}", path.display()))?;
+    Ok(serde_json::from_reader(file)?)
+}
+
+/// Prints one line per crash report: its name and the first line of the panic message.
+pub fn list(out: &mut impl Write) -> Result<()> {
+    let directory = crash_dir();
+    let names = report_names(&directory)?;
+    if names.is_empty() {
+        writeln!(out, "No crash reports in {}", directory.display())?;
+    }
+    for name in names {
+        let summary = match read_report(&directory, &name) {
+            Ok(report) => panic_summary(&report.panic),
+            Err(err) => format!("unreadable: {err}"),
+        };
+        writeln!(out, "{name}  {summary}")?;
+    }
+    Ok(())
+}
+
+/// Prints the crash report called `name`, or the most recent one.
+pub fn show(name: Option<String>, json: bool, out: &mut impl Write) -> Result<()> {
+    let directory = crash_dir();
+    let name = match name {
+        Some(name) => name,
+        None => report_names(&directory)?
+            .pop()
+            .ok_or_else(|| eyre!("No crash reports in {}", directory.display()))?,
+    };
+    let report = read_report(&directory, &name)?;
+    if json {
+        serde_json::to_writer_pretty(&mut *out, &report)?;
+        writeln!(out)?;
+        Ok(())
+    } else {
+        report.write_text(out)
+    }
+}
+
+/// The line of the panic report saying what panicked, e.g. `The application panicked
+/// (crashed).` is followed by `Message: ...`.
+fn panic_summary(panic: &str) -> String {
+    panic
+        .lines()
+        .map(str::trim)
+        .find(|line| line.starts_with("Message:"))
+        .or_else(|| panic.lines().map(str::trim).find(|line| !line.is_empty()))
+        .unwrap_or_default()
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_capture_and_read() -> Result<()> {
+        let state = AppState::new();
+        state.increment_count_by(3)?;
+        set_context(state, &Config::default());
+        for amount in 0..CRASH_ACTION_HISTORY as i32 + 2 {
+            record_action(&Action::IncrementBy(amount));
+        }
+
+        let report = CrashReport::capture(
+            "The application panicked (crashed).\nMessage:  boom\nLocation: src/app.rs:1".into(),
+        );
+        assert_eq!(report.recent_actions.len(), CRASH_ACTION_HISTORY);
+        assert_eq!(report.recent_actions[0], "IncrementBy 2");
+        assert_eq!(report.state.as_ref().map(|state| state.count), Some(3));
+        assert_eq!(panic_summary(&report.panic), "Message:  boom");
+
+        let directory = std::env::temp_dir().join(format!("crash-test-{}", std::process::id()));
+        std::fs::create_dir_all(&directory)?;
+        for name in ["crash-20240102T000000Z-1", "crash-20240101T000000Z-1"] {
+            let file = std::fs::File::create(directory.join(format!("{name}.json")))?;
+            serde_json::to_writer(file, &report)?;
+        }
+        std::fs::File::create(directory.join("notes.json"))?;
+
+        let names = report_names(&directory)?;
+        assert_eq!(
+            names,
+            ["crash-20240101T000000Z-1", "crash-20240102T000000Z-1"]
+        );
+        assert_eq!(read_report(&directory, &names[1])?, report);
+
+        let mut text = Vec::new();
+        report.write_text(&mut text)?;
+        let text = String::from_utf8(text)?;
+        assert!(text.contains("State: count 3, amount 1, mode Home"));
+        assert!(text.contains("  IncrementBy 51\n"));
+
+        std::fs::remove_dir_all(&directory)?;
+        Ok(())
+    }
+}
@@ -15,10 +15,8 @@ pub fn init() -> Result<()> {
         .into_hooks();
     eyre_hook.install()?;
     std::panic::set_hook(Box::new(move |panic_info| {
-        if let Ok(mut t) = crate::tui::Tui::new() {
-            if let Err(r) = t.exit() {
-                error!("Unable to exit Terminal: {:?}", r);
-            }
+        if let Err(r) = crate::tui::restore() {
+            error!("Unable to restore the terminal: {:?}", r);
         }
 
         #[cfg(not(debug_assertions))]
@@ -32,7 +30,12 @@ pub fn init() -> Result<()> {
             eprintln!("{}", panic_hook.panic_report(panic_info)); // prints color-eyre stack trace to stderr
         }
         let msg = format!("{}", panic_hook.panic_report(panic_info));
-        error!("Error: {}", strip_ansi_escapes::strip_str(msg));
+        let msg = strip_ansi_escapes::strip_str(msg);
+        error!("Error: {}", msg);
+        match crate::crash::CrashReport::capture(msg).write() {
+            Ok(path) => eprintln!("Crash report written to {}", path.display()),
+            Err(err) => error!("Unable to write the crash report: {:?}", err),
+        }
 
         #[cfg(debug_assertions)]
         {
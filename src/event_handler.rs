@@ -1,51 +1,87 @@
 use color_eyre::Result;
-use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use crossterm::event::{KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
 
 use crate::{action::Action, event::Event, state::AppState};
 
+/// Number of repeat events after which a held counter key moves one more amount per repeat.
+const REPEAT_ACCELERATION_INTERVAL: u32 = 8;
+/// Upper bound on how many amounts a single repeat event of a held counter key applies.
+const MAX_REPEAT_MULTIPLIER: u32 = 10;
+
+/// What [`EventHandler::handle`] did with an event.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Handled {
+    /// The event was turned into an action.
+    Action(Action),
+    /// The event was fully handled, e.g. by updating [`AppState`] directly.
+    Consumed,
+    /// The event is left to keybindings and the default handling in `App`.
+    Ignored,
+}
+
 pub struct EventHandler {
     state: AppState,
+    repeat_count: u32,
 }
 
 impl EventHandler {
     pub fn new(state: AppState) -> Self {
-        Self { state }
+        Self {
+            state,
+            repeat_count: 0,
+        }
     }
 
-    pub fn handle(&self, event: Event) -> Result<Option<Action>> {
+    pub fn handle(&mut self, event: Event) -> Result<Handled> {
         match event {
             Event::Key(key) => self.handle_key_event(key),
-            _ => Ok(None),
+            _ => Ok(Handled::Ignored),
         }
     }
 
-    fn handle_key_event(&self, key: KeyEvent) -> Result<Option<Action>> {
+    fn repeat_multiplier(&self) -> i32 {
+        (1 + self.repeat_count / REPEAT_ACCELERATION_INTERVAL).min(MAX_REPEAT_MULTIPLIER) as i32
+    }
+
+    fn handle_key_event(&mut self, key: KeyEvent) -> Result<Handled> {
+        match key.kind {
+            // Releases are only reported with keyboard enhancement and are left to keybindings.
+            KeyEventKind::Release => {
+                self.repeat_count = 0;
+                return Ok(Handled::Ignored);
+            }
+            KeyEventKind::Repeat => self.repeat_count = self.repeat_count.saturating_add(1),
+            KeyEventKind::Press => self.repeat_count = 0,
+        }
+
         match key.code {
             KeyCode::Char('q') if key.modifiers.is_empty() => {
-                return Ok(Some(Action::Quit));
+                return Ok(Handled::Action(Action::Quit));
             }
             KeyCode::Char('h') if key.modifiers.is_empty() => {
-                return Ok(Some(Action::Help));
+                return Ok(Handled::Action(Action::Help));
             }
             // Counter controls
             KeyCode::Left => {
-                self.state.decrement_count()?;
-                return Ok(Some(Action::Render));
+                self.state
+                    .decrement_count_scaled(self.repeat_multiplier())?;
+                return Ok(Handled::Consumed);
             }
             KeyCode::Right => {
-                self.state.increment_count()?;
-                return Ok(Some(Action::Render));
+                self.state
+                    .increment_count_scaled(self.repeat_multiplier())?;
+                return Ok(Handled::Consumed);
             }
             KeyCode::Up => {
                 self.state.increment_amount()?;
-                return Ok(Some(Action::Render));
+                return Ok(Handled::Consumed);
             }
             KeyCode::Down => {
                 self.state.decrement_amount()?;
-                return Ok(Some(Action::Render));
+                return Ok(Handled::Consumed);
             }
             KeyCode::Esc => {
-                return Ok(Some(Action::Quit));
+                return Ok(Handled::Action(Action::Quit));
             }
             _ => {}
         }
@@ -54,15 +90,44 @@ impl EventHandler {
         if key.modifiers.contains(KeyModifiers::CONTROL) {
             match key.code {
                 KeyCode::Char('c') => {
-                    return Ok(Some(Action::Quit));
+                    return Ok(Handled::Action(Action::Quit));
                 }
                 KeyCode::Char('r') => {
-                    return Ok(Some(Action::Refresh));
+                    return Ok(Handled::Action(Action::Refresh));
                 }
                 _ => {}
             }
         }
 
-        Ok(None)
+        Ok(Handled::Ignored)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn key(code: KeyCode, kind: KeyEventKind) -> Event {
+        Event::Key(KeyEvent::new_with_kind(code, KeyModifiers::empty(), kind))
+    }
+
+    #[test]
+    fn test_held_key_accelerates() -> Result<()> {
+        let state = AppState::new();
+        let mut handler = EventHandler::new(state.clone());
+
+        handler.handle(key(KeyCode::Right, KeyEventKind::Press))?;
+        for _ in 0..REPEAT_ACCELERATION_INTERVAL {
+            handler.handle(key(KeyCode::Right, KeyEventKind::Repeat))?;
+        }
+        // One press and seven repeats at one amount each, then the eighth repeat moves by two.
+        assert_eq!(state.get_count()?, 10);
+
+        handler.handle(key(KeyCode::Right, KeyEventKind::Release))?;
+        handler.handle(key(KeyCode::Right, KeyEventKind::Press))?;
+        assert_eq!(state.get_count()?, 11);
+        Ok(())
     }
 }
@@ -0,0 +1,338 @@
+//! The `apply` command: runs actions against [`AppState`] without entering the TUI and prints the
+//! resulting state.
+
+use std::io::{BufRead, Write};
+
+use clap::ValueEnum;
+use color_eyre::{eyre::eyre, Result};
+use crossterm::event::KeyEvent;
+
+use crate::{
+    action::Action,
+    app::Mode,
+    config::{parse_key_sequence, Config},
+    event::Event,
+    event_handler::{EventHandler, Handled},
+    state::{AppState, SharedState},
+};
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// One `name: value` line per field
+    #[default]
+    Plain,
+    /// A single JSON object
+    Json,
+}
+
+/// One step of the input: an action in command syntax or JSON, or keys such as `<Right><Right>`.
+#[derive(Debug, Clone, PartialEq)]
+enum Input {
+    Action(Action),
+    Keys(Vec<KeyEvent>),
+}
+
+impl Input {
+    fn parse(raw: &str) -> Result<Self, String> {
+        if raw.starts_with('<') {
+            parse_key_sequence(raw).map(Input::Keys)
+        } else {
+            raw.parse().map(Input::Action)
+        }
+    }
+}
+
+/// Applies inputs the way the app does: keys go through the [`EventHandler`] on the tabs with the
+/// counter keys and then the keybindings of the current tab, and actions change the state through
+/// [`AppState::apply`] and switch tabs. There are no popups here.
+pub struct Headless {
+    state: AppState,
+    event_handler: EventHandler,
+    config: Config,
+    tab: Mode,
+    previous_tab: Mode,
+    should_quit: bool,
+}
+
+impl Headless {
+    pub fn new(config: Config) -> Self {
+        let state = AppState::new().with_counter(config.config.counter);
+        Self {
+            event_handler: EventHandler::new(state.clone()),
+            state,
+            config,
+            tab: Mode::Home,
+            previous_tab: Mode::Home,
+            should_quit: false,
+        }
+    }
+
+    fn apply_input(&mut self, input: Input) -> Result<()> {
+        match input {
+            Input::Action(action) => self.apply_action(action),
+            Input::Keys(keys) => self.apply_keys(keys),
+        }
+    }
+
+    fn apply_action(&mut self, action: Action) -> Result<()> {
+        if matches!(action, Action::Quit | Action::ForceQuit) {
+            self.should_quit = true;
+        }
+        match action {
+            // There are no background tasks here, so the count gets there at once.
+            Action::CountTo(target) => self.state.set_count(target),
+            Action::NextTab => self.select_tab(self.tab.cycle_tab(1)),
+            Action::PrevTab => self.select_tab(self.tab.cycle_tab(-1)),
+            Action::ToggleLogViewer => match self.tab {
+                Mode::Logs => self.select_tab(self.previous_tab),
+                _ => self.select_tab(Mode::Logs),
+            },
+            Action::SelectTab(tab) if Mode::TABS.contains(&tab) => self.select_tab(tab),
+            Action::SelectTab(mode) => Err(eyre!("{mode:?} is not a tab")),
+            action => self.state.apply(&action),
+        }
+    }
+
+    fn select_tab(&mut self, tab: Mode) -> Result<()> {
+        if tab != self.tab {
+            self.previous_tab = self.tab;
+            self.tab = tab;
+        }
+        self.state.set_mode(tab)
+    }
+
+    fn apply_keys(&mut self, keys: Vec<KeyEvent>) -> Result<()> {
+        // Keys that the event handler left to the keybindings, for multi-key combinations.
+        let mut pending = Vec::new();
+        for key in keys {
+            if self.should_quit {
+                break;
+            }
+            let handled = if self.tab.has_counter_keys() {
+                self.event_handler.handle(Event::Key(key))?
+            } else {
+                Handled::Ignored
+            };
+            match handled {
+                Handled::Action(action) => self.apply_action(action)?,
+                Handled::Consumed => {}
+                Handled::Ignored => {
+                    let Some(keymap) = self.config.keybindings.get(&self.tab) else {
+                        continue;
+                    };
+                    pending.push(key);
+                    let action = keymap
+                        .get(&vec![key])
+                        .or_else(|| keymap.get(&pending))
+                        .cloned();
+                    if let Some(action) = action {
+                        pending.clear();
+                        self.apply_action(action)?;
+                    } else if !keymap
+                        .keys()
+                        .any(|keys| keys.len() > pending.len() && keys.starts_with(&pending))
+                    {
+                        // No binding continues with these keys, so start over with the next one.
+                        pending.clear();
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Applies inputs until one of them quits.
+    fn apply_all(
+        &mut self,
+        inputs: impl IntoIterator<Item = (String, Result<String>)>,
+    ) -> Result<()> {
+        for (location, line) in inputs {
+            if self.should_quit {
+                break;
+            }
+            let line = line?;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let input = Input::parse(line).map_err(|err| eyre!("{location}: {err}"))?;
+            self.apply_input(input)?;
+        }
+        Ok(())
+    }
+
+    pub fn snapshot(&self) -> Result<SharedState> {
+        self.state.snapshot()
+    }
+}
+
+/// Applies `inputs`, or the lines of stdin if there are none or the only one is `-`, and writes
+/// the final state to `out`.
+pub fn run(inputs: Vec<String>, format: OutputFormat, out: &mut impl Write) -> Result<()> {
+    let mut headless = Headless::new(Config::new()?);
+    if inputs.is_empty() || inputs == ["-"] {
+        let lines = std::io::stdin()
+            .lock()
+            .lines()
+            .enumerate()
+            .map(|(index, line)| (format!("line {}", index + 1), line.map_err(Into::into)));
+        headless.apply_all(lines)?;
+    } else {
+        let arguments = inputs
+            .into_iter()
+            .enumerate()
+            .map(|(index, input)| (format!("argument {}", index + 1), Ok(input)));
+        headless.apply_all(arguments)?;
+    }
+    write_state(&headless.snapshot()?, format, out)
+}
+
+fn write_state(state: &SharedState, format: OutputFormat, out: &mut impl Write) -> Result<()> {
+    match format {
+        OutputFormat::Plain => {
+            writeln!(out, "count: {}", state.count)?;
+            writeln!(out, "amount: {}", state.amount)?;
+            writeln!(out, "mode: {:?}", state.mode)?;
+        }
+        OutputFormat::Json => {
+            serde_json::to_writer(&mut *out, state)?;
+            writeln!(out)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::config::{CounterConfig, Overflow};
+
+    /// Applies `inputs` with the default config, like the `apply` command does.
+    fn apply(inputs: &[&str]) -> Result<SharedState> {
+        let mut headless = Headless::new(Config::new()?);
+        headless.apply_all(
+            inputs
+                .iter()
+                .enumerate()
+                .map(|(index, input)| (format!("argument {}", index + 1), Ok(input.to_string()))),
+        )?;
+        headless.snapshot()
+    }
+
+    #[test]
+    fn test_actions_and_keys() -> Result<()> {
+        let state = apply(&[
+            "IncrementBy 5",
+            r#"{"DecrementBy": 2}"#,
+            "<Up><Right>",
+            "# comment",
+            "",
+        ])?;
+        assert_eq!(state.count, 5);
+        assert_eq!(state.amount, "2");
+        Ok(())
+    }
+
+    #[test]
+    fn test_counter_bounds() -> Result<()> {
+        let mut config = Config::default();
+        config.config.counter = CounterConfig {
+            min: 0,
+            max: 10,
+            overflow: Overflow::Wrap,
+        };
+        let mut headless = Headless::new(config);
+        for (input, count, amount) in [
+            ("SetAmount 4", 0, "4"),
+            ("<Right><Right><Right>", 1, "4"),
+            ("<Left>", 8, "4"),
+            ("SetCount 42", 10, "4"),
+            ("SetAmount -3", 10, "1"),
+            ("CountTo -7", 0, "1"),
+            ("CountTo 7", 7, "1"),
+            ("Reset", 0, "1"),
+        ] {
+            headless.apply_all([(input.to_string(), Ok(input.to_string()))])?;
+            let state = headless.snapshot()?;
+            assert_eq!(
+                (input, state.count, state.amount.as_str()),
+                (input, count, amount)
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_tabs() -> Result<()> {
+        let state = apply(&["SelectTab Counter", "IncrementBy 3", "<Tab>"])?;
+        assert_eq!((state.count, state.mode), (3, Mode::Logs));
+
+        // The counter keys only work on the tabs that show the counter.
+        let state = apply(&[
+            "<Right>",
+            "<4><Right>",
+            "<BackTab><Right>",
+            "ToggleLogViewer",
+        ])?;
+        assert_eq!((state.count, state.mode), (1, Mode::Settings));
+        let state = apply(&["<3>", "ToggleLogViewer", "PrevTab"])?;
+        assert_eq!(state.mode, Mode::Settings);
+
+        assert!(apply(&["SelectTab Help"]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_unbound_keys_do_not_block_combinations() -> Result<()> {
+        let mut config = Config::new()?;
+        config
+            .keybindings
+            .entry(Mode::Home)
+            .or_default()
+            .insert(parse_key_sequence("<g><g>").unwrap(), Action::Reset);
+        let mut headless = Headless::new(config);
+        for (input, count) in [
+            ("IncrementBy 5", 5),
+            ("<x><g><g>", 0),
+            ("IncrementBy 5", 5),
+            ("<g><x><g><g>", 0),
+        ] {
+            headless.apply_all([(input.to_string(), Ok(input.to_string()))])?;
+            assert_eq!((input, headless.snapshot()?.count), (input, count));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_quit_stops_applying() -> Result<()> {
+        let state = apply(&["IncrementBy 1", "<q>", "IncrementBy 10"])?;
+        assert_eq!(state.count, 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_input() {
+        let err = apply(&["IncrementBy 1", "Frobnicate"]).unwrap_err();
+        assert!(err.to_string().starts_with("argument 2:"));
+    }
+
+    #[test]
+    fn test_output_formats() -> Result<()> {
+        let state = apply(&["IncrementBy 3"])?;
+        let mut plain = Vec::new();
+        write_state(&state, OutputFormat::Plain, &mut plain)?;
+        assert_eq!(
+            String::from_utf8(plain)?,
+            "count: 3\namount: 1\nmode: Home\n"
+        );
+        let mut json = Vec::new();
+        write_state(&state, OutputFormat::Json, &mut json)?;
+        assert_eq!(
+            String::from_utf8(json)?,
+            "{\"count\":3,\"amount\":\"1\",\"mode\":\"Home\"}\n"
+        );
+        Ok(())
+    }
+}
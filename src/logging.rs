@@ -1,36 +1,317 @@
-use color_eyre::Result;
+use std::{
+    fs::{File, OpenOptions},
+    path::{Path, PathBuf},
+    sync::OnceLock,
+    time::{Duration, SystemTime},
+};
+
+use color_eyre::{eyre::eyre, Result};
+use tracing::{debug, warn};
 use tracing_error::ErrorLayer;
-use tracing_subscriber::{fmt, prelude::*, EnvFilter};
+use tracing_subscriber::{
+    fmt::{self, format::FmtSpan, MakeWriter},
+    prelude::*,
+    reload, EnvFilter, Layer, Registry,
+};
 
+use self::buffer::{LogBuffer, LOG_BUFFER_CAPACITY};
 use crate::config;
 
+pub mod buffer;
+
 lazy_static::lazy_static! {
     pub static ref LOG_ENV: String = format!("{}_LOG_LEVEL", config::PROJECT_NAME.clone());
-    pub static ref LOG_FILE: String = format!("{}.log", env!("CARGO_PKG_NAME"));
+    pub static ref LOG_FILE_PREFIX: String = format!("{}-", env!("CARGO_PKG_NAME"));
+    /// The most recent log events, shown by the log viewer.
+    pub static ref LOG_BUFFER: LogBuffer = LogBuffer::new(LOG_BUFFER_CAPACITY);
 }
 
-pub fn init() -> Result<()> {
+const LOG_FILE_EXTENSION: &str = "log";
+
+/// Swaps the filter of all log output, see [`set_level`].
+static FILTER_HANDLE: OnceLock<reload::Handle<EnvFilter, Registry>> = OnceLock::new();
+/// The file logged to in this session.
+static LOG_PATH: OnceLock<PathBuf> = OnceLock::new();
+
+/// Number of session logs kept in the data directory by default, including the current one.
+pub const DEFAULT_LOG_RETENTION_COUNT: usize = 10;
+/// Session logs older than this many days are deleted by default.
+pub const DEFAULT_LOG_RETENTION_DAYS: u64 = 14;
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// Format of the log file.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum LogFormat {
+    /// Human-readable lines
+    #[default]
+    Text,
+    /// One JSON object per line, including span timings for latency analysis
+    Json,
+}
+
+/// Where to log to and which session logs to keep.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogOptions {
+    /// Log to this file, appending to it, instead of a new session log in the data directory.
+    pub file: Option<PathBuf>,
+    /// Filter directive, e.g. `debug` or `ratatui_domic::app=trace`, taking precedence over the
+    /// `RUST_LOG` and `LOG_ENV` environment variables.
+    pub level: Option<String>,
+    pub format: LogFormat,
+    pub retention_count: usize,
+    pub retention_days: u64,
+}
+
+impl Default for LogOptions {
+    fn default() -> Self {
+        Self {
+            file: None,
+            level: None,
+            format: LogFormat::default(),
+            retention_count: DEFAULT_LOG_RETENTION_COUNT,
+            retention_days: DEFAULT_LOG_RETENTION_DAYS,
+        }
+    }
+}
+
+/// Starts logging to a file. Unless `options.file` is set, every session gets its own timestamped
+/// log in the data directory, so the log of a crashed session survives the next start, and old
+/// session logs are pruned according to the retention settings.
+pub fn init(options: &LogOptions) -> Result<()> {
     let directory = config::get_data_dir();
-    std::fs::create_dir_all(directory.clone())?;
-    let log_path = directory.join(LOG_FILE.clone());
-    let log_file = std::fs::File::create(log_path)?;
-    let env_filter = EnvFilter::builder().with_default_directive(tracing::Level::INFO.into());
-    // If the `RUST_LOG` environment variable is set, use that as the default, otherwise use the
-    // value of the `LOG_ENV` environment variable. If the `LOG_ENV` environment variable contains
-    // errors, then this will return an error.
-    let env_filter = env_filter
-        .try_from_env()
-        .or_else(|_| env_filter.with_env_var(LOG_ENV.clone()).from_env())?;
-    let file_subscriber = fmt::layer()
-        .with_file(true)
-        .with_line_number(true)
-        .with_writer(log_file)
-        .with_target(false)
-        .with_ansi(false)
-        .with_filter(env_filter);
+    let (log_path, log_file) = match &options.file {
+        Some(path) => {
+            if let Some(parent) = path
+                .parent()
+                .filter(|parent| !parent.as_os_str().is_empty())
+            {
+                std::fs::create_dir_all(parent)?;
+            }
+            let file = OpenOptions::new().create(true).append(true).open(path)?;
+            (path.clone(), file)
+        }
+        None => {
+            std::fs::create_dir_all(&directory)?;
+            create_session_log(&directory)?
+        }
+    };
+
+    let (filter, filter_handle) = reload::Layer::new(env_filter(options)?);
+    let _ = FILTER_HANDLE.set(filter_handle);
     tracing_subscriber::registry()
-        .with(file_subscriber)
+        .with(filter)
+        .with(file_layer(options.format, log_file))
+        .with(LOG_BUFFER.layer())
         .with(ErrorLayer::default())
         .try_init()?;
+    debug!("Logging to {}", log_path.display());
+    let _ = LOG_PATH.set(log_path.clone());
+
+    if options.file.is_none() {
+        let max_age = Duration::from_secs(options.retention_days.saturating_mul(SECONDS_PER_DAY));
+        match prune_session_logs(&directory, &log_path, options.retention_count, max_age) {
+            Ok(removed) if !removed.is_empty() => debug!("Removed old logs {removed:?}"),
+            Ok(_) => {}
+            Err(err) => warn!("Unable to remove old logs: {err}"),
+        }
+    }
     Ok(())
 }
+
+/// The file logged to in this session, or `None` before logging is initialized.
+pub fn log_path() -> Option<PathBuf> {
+    LOG_PATH.get().cloned()
+}
+
+/// Replaces the filter directive of the log file and the log viewer while the app runs, e.g. with
+/// `debug` or `info,ratatui_domic::tui=trace`.
+pub fn set_level(directive: &str) -> Result<()> {
+    let filter = EnvFilter::builder()
+        .with_default_directive(tracing::Level::INFO.into())
+        .parse(directive)?;
+    FILTER_HANDLE
+        .get()
+        .ok_or_else(|| eyre!("Logging is not initialized"))?
+        .reload(filter)?;
+    Ok(())
+}
+
+/// The active filter directive, or `None` before logging is initialized.
+pub fn level() -> Option<String> {
+    FILTER_HANDLE
+        .get()?
+        .with_current(|filter| filter.to_string())
+        .ok()
+}
+
+/// The layer writing to the log file. The JSON format also logs every closed span with its busy and
+/// idle time, e.g. the `render` span of `App` and the `draw` spans of the components within it.
+fn file_layer<S, W>(format: LogFormat, writer: W) -> Box<dyn Layer<S> + Send + Sync>
+where
+    S: tracing::Subscriber + for<'a> tracing_subscriber::registry::LookupSpan<'a>,
+    W: for<'writer> MakeWriter<'writer> + Send + Sync + 'static,
+{
+    let layer = fmt::layer()
+        .with_file(true)
+        .with_line_number(true)
+        .with_writer(writer)
+        .with_ansi(false);
+    match format {
+        LogFormat::Text => layer.with_target(false).boxed(),
+        LogFormat::Json => layer
+            .json()
+            .with_current_span(true)
+            .with_span_list(true)
+            .with_span_events(FmtSpan::CLOSE)
+            .boxed(),
+    }
+}
+
+fn env_filter(options: &LogOptions) -> Result<EnvFilter> {
+    let env_filter = EnvFilter::builder().with_default_directive(tracing::Level::INFO.into());
+    // A level given on the command line wins. Otherwise, if the `RUST_LOG` environment variable is
+    // set, use that as the default, or else the value of the `LOG_ENV` environment variable. If the
+    // chosen directive contains errors, then this will return an error.
+    Ok(match &options.level {
+        Some(level) => env_filter.parse(level)?,
+        None => env_filter
+            .try_from_env()
+            .or_else(|_| env_filter.with_env_var(LOG_ENV.clone()).from_env())?,
+    })
+}
+
+/// Creates `<crate>-<UTC timestamp>-<pid>.log`, which sorts by start time.
+fn create_session_log(directory: &Path) -> Result<(PathBuf, File)> {
+    let timestamp = jiff::Timestamp::now().strftime("%Y%m%dT%H%M%SZ");
+    let path = directory.join(format!(
+        "{}{timestamp}-{}.{LOG_FILE_EXTENSION}",
+        LOG_FILE_PREFIX.as_str(),
+        std::process::id()
+    ));
+    let file = File::create(&path)?;
+    Ok((path, file))
+}
+
+/// Deletes session logs beyond the newest `keep` ones and those not modified within `max_age`.
+/// The current log is never deleted. Returns the deleted paths.
+fn prune_session_logs(
+    directory: &Path,
+    current: &Path,
+    keep: usize,
+    max_age: Duration,
+) -> Result<Vec<PathBuf>> {
+    let mut logs = std::fs::read_dir(directory)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| is_session_log(path) && path != current)
+        .collect::<Vec<_>>();
+    // Newest first; the timestamp in the name sorts chronologically.
+    logs.sort_by(|a, b| b.cmp(a));
+
+    let now = SystemTime::now();
+    let mut removed = Vec::new();
+    for (index, path) in logs.into_iter().enumerate() {
+        // The current log takes one of the `keep` slots.
+        let too_many = index + 1 >= keep;
+        let too_old = std::fs::metadata(&path)
+            .and_then(|metadata| metadata.modified())
+            .is_ok_and(|modified| now.duration_since(modified).unwrap_or_default() > max_age);
+        if too_many || too_old {
+            std::fs::remove_file(&path)?;
+            removed.push(path);
+        }
+    }
+    Ok(removed)
+}
+
+fn is_session_log(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext == LOG_FILE_EXTENSION)
+        && path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| name.starts_with(LOG_FILE_PREFIX.as_str()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_json_spans() -> Result<()> {
+        let output = Arc::new(Mutex::new(Vec::new()));
+        let writer = output.clone();
+        let make_writer = move || SharedWriter(writer.clone());
+        let subscriber =
+            tracing_subscriber::registry().with(file_layer(LogFormat::Json, make_writer));
+        tracing::subscriber::with_default(subscriber, || {
+            let _render = tracing::debug_span!("render").entered();
+            let _draw = tracing::trace_span!("draw", component = "Counter").entered();
+            tracing::info!("drawing");
+        });
+
+        let output = String::from_utf8(output.lock().unwrap().clone())?;
+        let lines = output
+            .lines()
+            .map(serde_json::from_str)
+            .collect::<Result<Vec<serde_json::Value>, _>>()?;
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0]["fields"]["message"], "drawing");
+        assert_eq!(lines[0]["span"]["component"], "Counter");
+        assert_eq!(lines[0]["spans"][0]["name"], "render");
+        assert_eq!(lines[1]["fields"]["message"], "close");
+        assert_eq!(lines[1]["span"]["name"], "draw");
+        assert!(lines[1]["fields"]["time.busy"].is_string());
+        Ok(())
+    }
+
+    #[derive(Clone)]
+    struct SharedWriter(Arc<Mutex<Vec<u8>>>);
+
+    impl std::io::Write for SharedWriter {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_prune_session_logs() -> Result<()> {
+        let directory = std::env::temp_dir().join(format!("logging-test-{}", std::process::id()));
+        std::fs::create_dir_all(&directory)?;
+        let log = |name: &str| directory.join(format!("{}{name}.log", LOG_FILE_PREFIX.as_str()));
+        let day = Duration::from_secs(24 * 60 * 60);
+        let long_ago = SystemTime::now() - 30 * day;
+        for name in [
+            "20240101T000000Z-1",
+            "20240102T000000Z-1",
+            "20240103T000000Z-1",
+        ] {
+            File::create(log(name))?.set_modified(long_ago)?;
+        }
+        let unrelated = directory.join("notes.log");
+        File::create(&unrelated)?;
+        let current = log("20240104T000000Z-1");
+        File::create(&current)?;
+
+        let removed = prune_session_logs(&directory, &current, 3, 60 * day)?;
+        assert_eq!(removed, vec![log("20240101T000000Z-1")]);
+
+        let removed = prune_session_logs(&directory, &current, 10, day)?;
+        assert_eq!(
+            removed,
+            vec![log("20240103T000000Z-1"), log("20240102T000000Z-1")]
+        );
+        assert!(current.exists() && unrelated.exists());
+
+        std::fs::remove_dir_all(&directory)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,156 @@
+//! Keeps the most recent log events in memory for the in-app log viewer.
+
+use std::{
+    collections::VecDeque,
+    fmt::Write,
+    sync::{Arc, Mutex},
+    time::SystemTime,
+};
+
+use tracing::{field::Field, Event, Level, Subscriber};
+use tracing_subscriber::{field::Visit, layer::Context, Layer};
+
+/// Number of events kept by [`LOG_BUFFER`](super::LOG_BUFFER).
+pub const LOG_BUFFER_CAPACITY: usize = 2000;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogRecord {
+    pub time: SystemTime,
+    pub level: Level,
+    pub target: String,
+    /// The message followed by the other fields as `name=value`.
+    pub message: String,
+}
+
+#[derive(Debug, Default)]
+struct Records {
+    records: VecDeque<LogRecord>,
+    /// Number of records pushed so far, including the ones that were dropped.
+    generation: u64,
+}
+
+/// A bounded ring buffer of log records; the oldest record is dropped when it is full.
+#[derive(Debug, Clone)]
+pub struct LogBuffer {
+    records: Arc<Mutex<Records>>,
+    capacity: usize,
+}
+
+impl LogBuffer {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            records: Arc::new(Mutex::new(Records::default())),
+            capacity,
+        }
+    }
+
+    pub fn push(&self, record: LogRecord) {
+        // Logging must not fail, and a poisoned buffer only means a record went missing.
+        let Ok(mut records) = self.records.lock() else {
+            return;
+        };
+        if records.records.len() == self.capacity {
+            records.records.pop_front();
+        }
+        records.records.push_back(record);
+        records.generation += 1;
+    }
+
+    /// Changes whenever a record is pushed.
+    pub fn generation(&self) -> u64 {
+        self.records.lock().map_or(0, |records| records.generation)
+    }
+
+    /// The buffered records, oldest first.
+    pub fn records(&self) -> Vec<LogRecord> {
+        self.records
+            .lock()
+            .map(|records| records.records.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// A [`Layer`] that pushes every event it sees into this buffer.
+    pub fn layer(&self) -> LogBufferLayer {
+        LogBufferLayer {
+            buffer: self.clone(),
+        }
+    }
+}
+
+pub struct LogBufferLayer {
+    buffer: LogBuffer,
+}
+
+impl<S: Subscriber> Layer<S> for LogBufferLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let mut visitor = MessageVisitor::default();
+        event.record(&mut visitor);
+        let metadata = event.metadata();
+        self.buffer.push(LogRecord {
+            time: SystemTime::now(),
+            level: *metadata.level(),
+            target: metadata.target().to_string(),
+            message: visitor.message + &visitor.fields,
+        });
+    }
+}
+
+#[derive(Default)]
+struct MessageVisitor {
+    message: String,
+    fields: String,
+}
+
+impl Visit for MessageVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.message.push_str(value);
+        } else {
+            let _ = write!(self.fields, " {}={value}", field.name());
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "message" {
+            let _ = write!(self.message, "{value:?}");
+        } else {
+            let _ = write!(self.fields, " {}={value:?}", field.name());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use tracing_subscriber::prelude::*;
+
+    use super::*;
+
+    #[test]
+    fn test_captures_events() {
+        let buffer = LogBuffer::new(2);
+        let subscriber = tracing_subscriber::registry().with(buffer.layer());
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::info!("first");
+            tracing::warn!(target: "app", count = 3, "second {}", 2);
+            tracing::error!(name = "x", "third");
+        });
+
+        let records = buffer.records();
+        assert_eq!(buffer.generation(), 3);
+        assert_eq!(
+            records
+                .iter()
+                .map(|record| (
+                    record.level,
+                    record.target.as_str(),
+                    record.message.as_str()
+                ))
+                .collect::<Vec<_>>(),
+            vec![
+                (Level::WARN, "app", "second 2 count=3"),
+                (Level::ERROR, module_path!(), "third name=x"),
+            ]
+        );
+    }
+}
@@ -1,28 +1,55 @@
 use clap::Parser;
-use cli::Cli;
+use cli::{Cli, Command, CrashCommand};
 use color_eyre::Result;
 
 use crate::app::App;
 
 mod action;
+mod action_queue;
 mod app;
 mod cli;
+mod commands;
 mod components;
 mod config;
+mod control;
+mod crash;
 mod errors;
 mod event;
+mod event_handler;
+mod headless;
 mod logging;
-mod tui;
+mod metrics;
+mod signals;
 mod state;
-mod event_handler;
+mod tasks;
+mod tui;
 
 #[tokio::main]
 async fn main() -> Result<()> {
     crate::errors::init()?;
-    crate::logging::init()?;
-
     let args = Cli::parse();
-    let mut app = App::new(args.tick_rate, args.frame_rate)?;
+    crate::logging::init(&args.log_options())?;
+
+    match args.command {
+        Some(Command::Apply { actions, format }) => {
+            return headless::run(actions, format, &mut std::io::stdout().lock());
+        }
+        Some(Command::Crashes { command }) => {
+            let out = &mut std::io::stdout().lock();
+            return match command {
+                CrashCommand::List => crash::list(out),
+                CrashCommand::Show { name, json } => crash::show(name, json, out),
+            };
+        }
+        None => {}
+    }
+    let mut app = App::new(args.tick_rate, args.frame_rate)?
+        .keyboard_enhancement(args.keyboard_enhancement)
+        .inline(args.inline)
+        .queue_capacity(args.queue_capacity)
+        .commands(args.commands)
+        .command_input(args.command_input)
+        .control_socket(args.control_socket);
     app.run().await?;
     Ok(())
 }
@@ -0,0 +1,120 @@
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+/// Number of frames kept for percentiles and the frame time sparkline.
+pub const FRAME_HISTORY: usize = 240;
+
+/// Render timings and queue statistics recorded by the app loop, shared with components such as
+/// the [`FpsCounter`](crate::components::fps::FpsCounter) that report them.
+#[derive(Clone, Debug, Default)]
+pub struct Metrics {
+    inner: Arc<Mutex<MetricsSnapshot>>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MetricsSnapshot {
+    /// Durations of the most recent frames, oldest first.
+    pub frame_times: VecDeque<Duration>,
+    /// How long each component took to draw in the last frame, in drawing order.
+    pub draw_times: Vec<(String, Duration)>,
+    /// Number of actions waiting when the app last started draining the action queue.
+    pub queue_depth: usize,
+    /// The largest queue depth seen so far.
+    pub max_queue_depth: usize,
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record_frame(&self, frame_time: Duration, draw_times: Vec<(String, Duration)>) {
+        let Ok(mut inner) = self.inner.lock() else {
+            return;
+        };
+        if inner.frame_times.len() == FRAME_HISTORY {
+            inner.frame_times.pop_front();
+        }
+        inner.frame_times.push_back(frame_time);
+        inner.draw_times = draw_times;
+    }
+
+    pub fn record_queue_depth(&self, depth: usize) {
+        let Ok(mut inner) = self.inner.lock() else {
+            return;
+        };
+        inner.queue_depth = depth;
+        inner.max_queue_depth = inner.max_queue_depth.max(depth);
+    }
+
+    pub fn snapshot(&self) -> MetricsSnapshot {
+        self.inner
+            .lock()
+            .map(|inner| inner.clone())
+            .unwrap_or_default()
+    }
+}
+
+impl MetricsSnapshot {
+    /// The frame time below which `percentile` percent of the recorded frames fall, using the
+    /// nearest-rank method.
+    pub fn frame_time_percentile(&self, percentile: f64) -> Option<Duration> {
+        let mut sorted = self.frame_times.iter().copied().collect::<Vec<_>>();
+        sorted.sort_unstable();
+        percentile_of_sorted(&sorted, percentile)
+    }
+}
+
+fn percentile_of_sorted(sorted: &[Duration], percentile: f64) -> Option<Duration> {
+    if sorted.is_empty() {
+        return None;
+    }
+    let rank = (percentile / 100.0 * sorted.len() as f64).ceil() as usize;
+    Some(sorted[rank.clamp(1, sorted.len()) - 1])
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_frame_time_percentiles() {
+        let metrics = Metrics::new();
+        assert_eq!(metrics.snapshot().frame_time_percentile(50.0), None);
+        for ms in (1..=100).rev() {
+            metrics.record_frame(Duration::from_millis(ms), Vec::new());
+        }
+        let snapshot = metrics.snapshot();
+        assert_eq!(
+            snapshot.frame_time_percentile(50.0),
+            Some(Duration::from_millis(50))
+        );
+        assert_eq!(
+            snapshot.frame_time_percentile(99.0),
+            Some(Duration::from_millis(99))
+        );
+        assert_eq!(
+            snapshot.frame_time_percentile(0.0),
+            Some(Duration::from_millis(1))
+        );
+    }
+
+    #[test]
+    fn test_history_is_bounded() {
+        let metrics = Metrics::new();
+        for _ in 0..FRAME_HISTORY + 10 {
+            metrics.record_frame(Duration::from_millis(1), Vec::new());
+        }
+        metrics.record_queue_depth(7);
+        metrics.record_queue_depth(2);
+        let snapshot = metrics.snapshot();
+        assert_eq!(snapshot.frame_times.len(), FRAME_HISTORY);
+        assert_eq!(snapshot.queue_depth, 2);
+        assert_eq!(snapshot.max_queue_depth, 7);
+    }
+}
@@ -0,0 +1,105 @@
+//! Termination and job control signals.
+//!
+//! The first SIGTERM, SIGINT or SIGHUP asks the app to shut down with [`Action::ForceQuit`], which
+//! skips the quit confirmation. If another one arrives before the app is done, e.g. because the app
+//! loop is stuck, the terminal is restored right away and the process exits.
+//!
+//! SIGTSTP, e.g. from `kill -TSTP`, suspends the app like `<Ctrl-z>` does. When the app was stopped
+//! from outside, e.g. with SIGSTOP, SIGCONT is turned into [`Action::Resume`]; when it stopped
+//! itself, it resumes on its own once continued.
+
+use color_eyre::Result;
+#[cfg(unix)]
+use tracing::{debug, error, info};
+
+use crate::action_queue::ActionSender;
+#[cfg(unix)]
+use crate::{action::Action, tui};
+
+/// Stops handling signals when closed.
+pub struct SignalHandler {
+    #[cfg(unix)]
+    handle: signal_hook::iterator::Handle,
+}
+
+impl SignalHandler {
+    pub fn close(&self) {
+        #[cfg(unix)]
+        self.handle.close();
+    }
+}
+
+#[cfg(unix)]
+pub fn spawn_signal_handler(action_tx: ActionSender) -> Result<SignalHandler> {
+    use signal_hook::{
+        consts::signal::{SIGCONT, SIGHUP, SIGINT, SIGTERM, SIGTSTP},
+        iterator::Signals,
+    };
+
+    let mut signals = Signals::new([SIGTERM, SIGINT, SIGHUP, SIGTSTP, SIGCONT])?;
+    let handle = signals.handle();
+    std::thread::spawn(move || {
+        let mut quitting = false;
+        for signal in signals.forever() {
+            let action = match signal {
+                SIGTSTP => Action::Suspend,
+                // The app resumes on its own after stopping itself.
+                SIGCONT if tui::take_self_suspension() => continue,
+                SIGCONT => Action::Resume,
+                _ if quitting => {
+                    error!("Received signal {signal} again, exiting immediately");
+                    if let Err(err) = tui::restore() {
+                        error!("Unable to restore the terminal: {err:?}");
+                    }
+                    std::process::exit(128 + signal);
+                }
+                _ => {
+                    info!("Received signal {signal}, shutting down");
+                    quitting = true;
+                    Action::ForceQuit
+                }
+            };
+            debug!("Signal {signal}: {action:?}");
+            // Never dropped, even when the queue is full, and never waits for the app loop, which
+            // keeps a second signal working while the loop is stuck.
+            if action_tx.send(action).is_err() {
+                break;
+            }
+        }
+    });
+    Ok(SignalHandler { handle })
+}
+
+#[cfg(not(unix))]
+pub fn spawn_signal_handler(_action_tx: ActionSender) -> Result<SignalHandler> {
+    Ok(SignalHandler {})
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use std::time::Duration;
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::action_queue::{self, DEFAULT_QUEUE_CAPACITY};
+
+    #[test]
+    fn test_signal_requests_quit() -> Result<()> {
+        let (action_tx, mut action_rx) = action_queue::channel(DEFAULT_QUEUE_CAPACITY);
+        let handler = spawn_signal_handler(action_tx)?;
+        signal_hook::low_level::raise(signal_hook::consts::signal::SIGHUP)?;
+
+        let mut action = None;
+        for _ in 0..100 {
+            action = action_rx.try_recv();
+            if action.is_some() {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        handler.close();
+        assert_eq!(action, Some(Action::ForceQuit));
+        Ok(())
+    }
+}
@@ -1,16 +1,30 @@
-use std::sync::{Arc, Mutex};
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc, Mutex,
+};
 
 use color_eyre::Result;
+use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Debug, PartialEq)]
-struct SharedState {
+use crate::{action::Action, app::Mode, config::CounterConfig};
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SharedState {
     pub count: i32,
     pub amount: String,
+    pub mode: Mode,
+    /// Keys typed so far of a multi-key binding, e.g. `<g>`. Only of interest to the UI.
+    #[serde(skip)]
+    pub pending_keys: String,
 }
 
 #[derive(Clone, Debug)]
 pub struct AppState {
     shared: Arc<Mutex<SharedState>>,
+    /// Bumped on every mutation so that the UI can tell whether it needs to redraw.
+    revision: Arc<AtomicU64>,
+    /// The range the count is kept in.
+    counter: CounterConfig,
 }
 
 impl PartialEq for AppState {
@@ -28,7 +42,11 @@ impl Default for AppState {
             shared: Arc::new(Mutex::new(SharedState {
                 count: 0,
                 amount: "1".to_string(),
+                mode: Mode::default(),
+                pending_keys: String::new(),
             })),
+            revision: Arc::new(AtomicU64::new(0)),
+            counter: CounterConfig::default(),
         }
     }
 }
@@ -38,38 +56,156 @@ impl AppState {
         Self::default()
     }
 
+    /// Keeps the count within the bounds of `counter`, and moves it there right away.
+    pub fn with_counter(mut self, counter: CounterConfig) -> Self {
+        self.counter = counter;
+        if let Ok(mut state) = self.shared.lock() {
+            state.count = counter.clamp(state.count);
+        }
+        self
+    }
+
+    /// Applies the effect an action has on the state, if any. Shared by the app loop and the
+    /// headless `apply` command, so that both give the same result.
+    pub fn apply(&self, action: &Action) -> Result<()> {
+        match action {
+            Action::IncrementBy(amount) => self.increment_count_by(*amount),
+            Action::DecrementBy(amount) => self.decrement_count_by(*amount),
+            Action::Reset => self.reset(),
+            Action::SetCount(count) => self.set_count(*count),
+            Action::SetAmount(amount) => self.set_amount(amount.max(&1).to_string()),
+            Action::Confirmed(action) => self.apply(action),
+            _ => Ok(()),
+        }
+    }
+
+    /// A counter that changes whenever the state is modified.
+    pub fn revision(&self) -> u64 {
+        self.revision.load(Ordering::Acquire)
+    }
+
+    fn touch(&self) {
+        self.revision.fetch_add(1, Ordering::AcqRel);
+    }
+
+    /// A copy of the current state, e.g. for reporting it outside the app.
+    pub fn snapshot(&self) -> Result<SharedState> {
+        let state = self
+            .shared
+            .lock()
+            .map_err(|_| color_eyre::eyre::eyre!("Failed to lock state"))?;
+        Ok(state.clone())
+    }
+
+    /// Like [`AppState::snapshot`], but gives up instead of waiting if the state is locked, e.g.
+    /// from a panic hook.
+    pub fn try_snapshot(&self) -> Option<SharedState> {
+        self.shared.try_lock().ok().map(|state| state.clone())
+    }
+
+    pub fn set_mode(&self, mode: Mode) -> Result<()> {
+        let mut state = self
+            .shared
+            .lock()
+            .map_err(|_| color_eyre::eyre::eyre!("Failed to lock state"))?;
+        state.mode = mode;
+        self.touch();
+        Ok(())
+    }
+
     pub fn get_count(&self) -> Result<i32> {
-        let state = self.shared.lock().map_err(|_| color_eyre::eyre::eyre!("Failed to lock state"))?;
+        let state = self
+            .shared
+            .lock()
+            .map_err(|_| color_eyre::eyre::eyre!("Failed to lock state"))?;
         Ok(state.count)
     }
 
+    /// Sets the count, clamped to the bounds.
     pub fn set_count(&self, count: i32) -> Result<()> {
-        let mut state = self.shared.lock().map_err(|_| color_eyre::eyre::eyre!("Failed to lock state"))?;
-        state.count = count;
+        let mut state = self
+            .shared
+            .lock()
+            .map_err(|_| color_eyre::eyre::eyre!("Failed to lock state"))?;
+        state.count = self.counter.clamp(count);
+        self.touch();
+        Ok(())
+    }
+
+    /// Moves the count by `offset`, clamping or wrapping at the bounds.
+    fn move_count(&self, offset: i64) -> Result<()> {
+        let mut state = self
+            .shared
+            .lock()
+            .map_err(|_| color_eyre::eyre::eyre!("Failed to lock state"))?;
+        state.count = self.counter.bound(i64::from(state.count) + offset);
+        self.touch();
         Ok(())
     }
 
+    /// Sets the count back to zero, or the bound nearest to it, and the amount back to one.
+    pub fn reset(&self) -> Result<()> {
+        self.set_count(0)?;
+        self.set_amount("1".to_string())
+    }
+
     pub fn get_amount(&self) -> Result<String> {
-        let state = self.shared.lock().map_err(|_| color_eyre::eyre::eyre!("Failed to lock state"))?;
+        let state = self
+            .shared
+            .lock()
+            .map_err(|_| color_eyre::eyre::eyre!("Failed to lock state"))?;
         Ok(state.amount.clone())
     }
 
     pub fn set_amount(&self, amount: String) -> Result<()> {
-        let mut state = self.shared.lock().map_err(|_| color_eyre::eyre::eyre!("Failed to lock state"))?;
+        let mut state = self
+            .shared
+            .lock()
+            .map_err(|_| color_eyre::eyre::eyre!("Failed to lock state"))?;
         state.amount = amount;
+        self.touch();
+        Ok(())
+    }
+
+    pub fn get_pending_keys(&self) -> Result<String> {
+        let state = self
+            .shared
+            .lock()
+            .map_err(|_| color_eyre::eyre::eyre!("Failed to lock state"))?;
+        Ok(state.pending_keys.clone())
+    }
+
+    /// Only touches the state if the pending keys change, since this is called on every tick.
+    pub fn set_pending_keys(&self, pending_keys: String) -> Result<()> {
+        let mut state = self
+            .shared
+            .lock()
+            .map_err(|_| color_eyre::eyre::eyre!("Failed to lock state"))?;
+        if state.pending_keys != pending_keys {
+            state.pending_keys = pending_keys;
+            self.touch();
+        }
         Ok(())
     }
 
-    pub fn increment_count(&self) -> Result<()> {
+    pub fn increment_count_by(&self, amount: i32) -> Result<()> {
+        self.move_count(i64::from(amount))
+    }
+
+    pub fn decrement_count_by(&self, amount: i32) -> Result<()> {
+        self.move_count(-i64::from(amount))
+    }
+
+    /// Increment the count by `factor` times the current amount.
+    pub fn increment_count_scaled(&self, factor: i32) -> Result<()> {
         let amount = self.get_amount()?.parse::<i32>().unwrap_or(1);
-        let current_count = self.get_count()?;
-        self.set_count(current_count.saturating_add(amount))
+        self.move_count(i64::from(amount) * i64::from(factor))
     }
 
-    pub fn decrement_count(&self) -> Result<()> {
+    /// Decrement the count by `factor` times the current amount.
+    pub fn decrement_count_scaled(&self, factor: i32) -> Result<()> {
         let amount = self.get_amount()?.parse::<i32>().unwrap_or(1);
-        let current_count = self.get_count()?;
-        self.set_count(current_count.saturating_sub(amount))
+        self.move_count(-i64::from(amount) * i64::from(factor))
     }
 
     pub fn increment_amount(&self) -> Result<()> {
@@ -0,0 +1,288 @@
+//! Background tasks spawned on the tokio runtime, e.g. for [`Action::CountTo`].
+//!
+//! Tasks report back through the action queue: [`Action::TaskProgress`] while running, the action
+//! they return as their result, and finally [`Action::TaskFinished`] or
+//! [`Action::TaskCancelled`]. Errors are reported as [`Action::Error`]. A task is cancelled when it
+//! is cancelled by id, when the app switches to another [`Mode`](crate::app::Mode), or when the app
+//! shuts down.
+
+use std::{
+    collections::HashMap,
+    future::Future,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use color_eyre::Result;
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, warn};
+
+use crate::{action::Action, action_queue::ActionSender};
+
+/// Passed to a running task to report back. The task's future is dropped when it is cancelled.
+#[derive(Debug, Clone)]
+pub struct TaskContext {
+    id: u64,
+    action_tx: ActionSender,
+}
+
+impl TaskContext {
+    /// Reports progress as a fraction between 0 and 1.
+    pub async fn progress(&self, fraction: f64) -> Result<()> {
+        self.send(Action::TaskProgress(self.id, fraction.clamp(0.0, 1.0)))
+            .await
+    }
+
+    /// Sends an intermediate result. Waits while the action queue is full.
+    pub async fn send(&self, action: Action) -> Result<()> {
+        self.action_tx.send_wait(action).await
+    }
+}
+
+#[derive(Debug)]
+struct Task {
+    name: String,
+    cancellation_token: CancellationToken,
+    join_handle: Option<JoinHandle<()>>,
+}
+
+#[derive(Debug)]
+struct Tasks {
+    next_id: u64,
+    running: HashMap<u64, Task>,
+    /// Parent of the tokens of all tasks; cancelled on shutdown.
+    app_token: CancellationToken,
+    /// Parent of the tokens of the tasks spawned in the current mode; replaced on every mode
+    /// switch.
+    mode_token: CancellationToken,
+}
+
+/// Spawns and cancels background tasks. Handed to components through
+/// [`Component::register_task_handler`](crate::components::Component::register_task_handler)
+/// as well.
+#[derive(Debug, Clone)]
+pub struct TaskHandle {
+    tasks: Arc<Mutex<Tasks>>,
+    action_tx: ActionSender,
+}
+
+impl TaskHandle {
+    /// Spawns `task` and returns its id, which is used in the actions reporting on it.
+    ///
+    /// The action returned by the task, if any, is sent as its result.
+    pub fn spawn<F, Fut>(&self, name: &str, task: F) -> Result<u64>
+    where
+        F: FnOnce(TaskContext) -> Fut,
+        Fut: Future<Output = Result<Option<Action>>> + Send + 'static,
+    {
+        let (id, cancellation_token) = {
+            let mut tasks = lock(&self.tasks)?;
+            let id = tasks.next_id;
+            tasks.next_id += 1;
+            (id, tasks.mode_token.child_token())
+        };
+        let context = TaskContext {
+            id,
+            action_tx: self.action_tx.clone(),
+        };
+        // Created before locking again, in case the closure spawns tasks itself.
+        let future = task(context);
+
+        // Held until the task is registered, so that it cannot remove itself before that.
+        let mut tasks = lock(&self.tasks)?;
+        let handle = self.clone();
+        let token = cancellation_token.clone();
+        let task_name = name.to_string();
+        let join_handle = tokio::spawn(async move {
+            let actions = tokio::select! {
+                _ = token.cancelled() => {
+                    debug!("Task {id} ({task_name}) cancelled");
+                    vec![Action::TaskCancelled(id)]
+                }
+                result = future => {
+                    debug!("Task {id} ({task_name}) finished");
+                    match result {
+                        Ok(action) => action.into_iter().collect(),
+                        Err(err) => vec![Action::Error(format!("{task_name} failed: {err}"))],
+                    }
+                    .into_iter()
+                    .chain([Action::TaskFinished(id)])
+                    .collect()
+                }
+            };
+            for action in actions {
+                if handle.action_tx.send_wait(action).await.is_err() {
+                    break;
+                }
+            }
+            if let Ok(mut tasks) = lock(&handle.tasks) {
+                tasks.running.remove(&id);
+            }
+        });
+
+        tasks.running.insert(
+            id,
+            Task {
+                name: name.to_string(),
+                cancellation_token,
+                join_handle: Some(join_handle),
+            },
+        );
+        Ok(id)
+    }
+
+    /// Cancels a task. Does nothing if it already finished.
+    pub fn cancel(&self, id: u64) -> Result<()> {
+        if let Some(task) = lock(&self.tasks)?.running.get(&id) {
+            task.cancellation_token.cancel();
+        }
+        Ok(())
+    }
+
+    /// Names of the running tasks, ordered by id.
+    pub fn running(&self) -> Result<Vec<(u64, String)>> {
+        let tasks = lock(&self.tasks)?;
+        let mut running = tasks
+            .running
+            .iter()
+            .map(|(id, task)| (*id, task.name.clone()))
+            .collect::<Vec<_>>();
+        running.sort();
+        Ok(running)
+    }
+}
+
+/// Owned by the [`App`](crate::app::App), which ties task cancellation to mode switches and
+/// shutdown.
+#[derive(Debug)]
+pub struct TaskManager {
+    handle: TaskHandle,
+}
+
+impl TaskManager {
+    pub fn new(action_tx: ActionSender) -> Self {
+        let app_token = CancellationToken::new();
+        let mode_token = app_token.child_token();
+        Self {
+            handle: TaskHandle {
+                tasks: Arc::new(Mutex::new(Tasks {
+                    next_id: 0,
+                    running: HashMap::new(),
+                    app_token,
+                    mode_token,
+                })),
+                action_tx,
+            },
+        }
+    }
+
+    pub fn handle(&self) -> TaskHandle {
+        self.handle.clone()
+    }
+
+    /// Cancels the tasks spawned in the mode that is being left.
+    pub fn switch_mode(&self) -> Result<()> {
+        let mut tasks = lock(&self.handle.tasks)?;
+        tasks.mode_token.cancel();
+        tasks.mode_token = tasks.app_token.child_token();
+        Ok(())
+    }
+
+    /// Cancels all tasks and waits up to `timeout` for them to wind down, then aborts the rest.
+    pub async fn shutdown(&self, timeout: Duration) -> Result<()> {
+        let join_handles = {
+            let mut tasks = lock(&self.handle.tasks)?;
+            tasks.app_token.cancel();
+            tasks
+                .running
+                .values_mut()
+                .filter_map(|task| task.join_handle.take())
+                .collect::<Vec<_>>()
+        };
+        let abort_handles = join_handles
+            .iter()
+            .map(JoinHandle::abort_handle)
+            .collect::<Vec<_>>();
+        if tokio::time::timeout(timeout, futures::future::join_all(join_handles))
+            .await
+            .is_err()
+        {
+            warn!("Background tasks did not stop within {timeout:?}, aborting them");
+            abort_handles.iter().for_each(|handle| handle.abort());
+        }
+        Ok(())
+    }
+}
+
+fn lock(tasks: &Mutex<Tasks>) -> Result<std::sync::MutexGuard<'_, Tasks>> {
+    tasks
+        .lock()
+        .map_err(|_| color_eyre::eyre::eyre!("Failed to lock tasks"))
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::action_queue::{self, ActionReceiver, DEFAULT_QUEUE_CAPACITY};
+
+    async fn wait_until_idle(handle: &TaskHandle) {
+        while !handle.running().unwrap().is_empty() {
+            tokio::task::yield_now().await;
+        }
+    }
+
+    fn drain(action_rx: &mut ActionReceiver) -> Vec<Action> {
+        std::iter::from_fn(|| action_rx.try_recv()).collect()
+    }
+
+    #[tokio::test]
+    async fn test_progress_and_result() {
+        let (action_tx, mut action_rx) = action_queue::channel(DEFAULT_QUEUE_CAPACITY);
+        let manager = TaskManager::new(action_tx);
+        let handle = manager.handle();
+        let id = handle
+            .spawn("count", |context| async move {
+                context.progress(0.5).await?;
+                Ok(Some(Action::IncrementBy(2)))
+            })
+            .unwrap();
+        wait_until_idle(&handle).await;
+        assert_eq!(
+            drain(&mut action_rx),
+            vec![
+                Action::TaskProgress(id, 0.5),
+                Action::IncrementBy(2),
+                Action::TaskFinished(id)
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_cancellation() {
+        let (action_tx, mut action_rx) = action_queue::channel(DEFAULT_QUEUE_CAPACITY);
+        let manager = TaskManager::new(action_tx);
+        let handle = manager.handle();
+        let pending = |_| std::future::pending();
+        let first = handle.spawn("first", pending).unwrap();
+        let second = handle.spawn("second", pending).unwrap();
+
+        handle.cancel(first).unwrap();
+        while handle.running().unwrap().len() > 1 {
+            tokio::task::yield_now().await;
+        }
+        assert_eq!(handle.running().unwrap(), vec![(second, "second".into())]);
+        assert_eq!(drain(&mut action_rx), vec![Action::TaskCancelled(first)]);
+
+        manager.switch_mode().unwrap();
+        wait_until_idle(&handle).await;
+        assert_eq!(drain(&mut action_rx), vec![Action::TaskCancelled(second)]);
+
+        // Tasks spawned after a mode switch are cancelled on shutdown.
+        let third = handle.spawn("third", pending).unwrap();
+        manager.shutdown(Duration::from_secs(1)).await.unwrap();
+        assert_eq!(drain(&mut action_rx), vec![Action::TaskCancelled(third)]);
+    }
+}
@@ -2,56 +2,113 @@
 
 use std::{
     io::{stdout, Stdout},
+    num::NonZeroUsize,
     ops::{Deref, DerefMut},
+    sync::{
+        atomic::{AtomicBool, AtomicU8, Ordering},
+        Arc,
+    },
     time::Duration,
 };
 
-use color_eyre::Result;
+use color_eyre::{eyre::eyre, Result};
 use crossterm::{
     cursor,
     event::{
-        DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste, EnableMouseCapture,
-        Event as CrosstermEvent, EventStream, KeyEventKind,
+        DisableBracketedPaste, DisableFocusChange, DisableMouseCapture, EnableBracketedPaste,
+        EnableFocusChange, EnableMouseCapture, Event as CrosstermEvent, EventStream,
+        KeyboardEnhancementFlags, PopKeyboardEnhancementFlags, PushKeyboardEnhancementFlags,
     },
+    style::Print,
     terminal::{EnterAlternateScreen, LeaveAlternateScreen},
 };
 use futures::{FutureExt, StreamExt};
-use ratatui::backend::CrosstermBackend as Backend;
+use ratatui::{backend::CrosstermBackend as Backend, TerminalOptions, Viewport};
 use tokio::{
-    sync::mpsc::{self, UnboundedReceiver, UnboundedSender},
+    sync::{
+        mpsc::{self, error::TrySendError, Receiver, Sender},
+        watch, Notify,
+    },
     task::JoinHandle,
-    time::interval,
+    time::{interval, sleep_until, Instant},
 };
 use tokio_util::sync::CancellationToken;
-use tracing::error;
+use tracing::{debug, error, warn};
+
+use crate::{action_queue::DEFAULT_QUEUE_CAPACITY, event::Event};
 
-use crate::event::Event;
+/// Tick rate used while the terminal is unfocused, unless the configured rate is already lower.
+const UNFOCUSED_TICK_RATE: f64 = 1.0;
+/// Frame rate used while the terminal is unfocused, unless the configured rate is already lower.
+const UNFOCUSED_FRAME_RATE: f64 = 4.0;
+
+/// How long [`Tui::stop`] waits for the event loop to finish.
+const STOP_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// Checks that a tick or frame rate is a positive, finite number of events per second, with a
+/// period that a [`Duration`] can hold and that is not rounded down to zero.
+pub fn validate_rate(rate: f64) -> Result<f64> {
+    if !(rate.is_finite() && rate > 0.0) {
+        return Err(eyre!("Rate must be a positive number, got {rate}"));
+    }
+    match Duration::try_from_secs_f64(1.0 / rate) {
+        Ok(period) if !period.is_zero() => Ok(rate),
+        _ => Err(eyre!("Rate is out of range, got {rate}")),
+    }
+}
+
+/// Rates the event loop currently runs at.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Rates {
+    tick_rate: f64,
+    frame_rate: f64,
+}
 
 pub struct Tui {
     pub terminal: ratatui::Terminal<Backend<Stdout>>,
     pub task: JoinHandle<()>,
     pub cancellation_token: CancellationToken,
-    pub event_rx: UnboundedReceiver<Event>,
-    pub event_tx: UnboundedSender<Event>,
+    pub event_rx: Receiver<Event>,
+    pub event_tx: Sender<Event>,
+    render_requested: Arc<Notify>,
+    rates_tx: watch::Sender<Rates>,
     pub frame_rate: f64,
     pub tick_rate: f64,
+    /// Whether the rates are currently lowered because the terminal lost focus.
+    throttled: bool,
+    pub focus: bool,
     pub mouse: bool,
     pub paste: bool,
+    pub keyboard_enhancement: bool,
+    /// Height of the inline viewport, or `None` for the alternate screen.
+    pub inline_height: Option<u16>,
+    /// Whether this instance set up the terminal, which it then restores when dropped.
+    entered: bool,
 }
 
 impl Tui {
     pub fn new() -> Result<Self> {
-        let (event_tx, event_rx) = mpsc::unbounded_channel();
+        let (event_tx, event_rx) = mpsc::channel(DEFAULT_QUEUE_CAPACITY.get());
         Ok(Self {
             terminal: ratatui::Terminal::new(Backend::new(stdout()))?,
             task: tokio::spawn(async {}),
             cancellation_token: CancellationToken::new(),
             event_rx,
             event_tx,
+            render_requested: Arc::new(Notify::new()),
+            rates_tx: watch::Sender::new(Rates {
+                tick_rate: 4.0,
+                frame_rate: 60.0,
+            }),
             frame_rate: 60.0,
             tick_rate: 4.0,
+            throttled: false,
+            focus: false,
             mouse: false,
             paste: false,
+            keyboard_enhancement: false,
+            inline_height: None,
+            entered: false,
         })
     }
 
@@ -65,6 +122,18 @@ impl Tui {
         self
     }
 
+    /// Number of events that can be queued before the event loop waits for the app to catch up.
+    pub fn event_capacity(mut self, capacity: NonZeroUsize) -> Self {
+        (self.event_tx, self.event_rx) = mpsc::channel(capacity.get());
+        self
+    }
+
+    /// Report focus changes, which lower the tick and frame rates while the terminal is unfocused.
+    pub fn focus(mut self, focus: bool) -> Self {
+        self.focus = focus;
+        self
+    }
+
     pub fn mouse(mut self, mouse: bool) -> Self {
         self.mouse = mouse;
         self
@@ -75,14 +144,71 @@ impl Tui {
         self
     }
 
+    /// Draw into an inline viewport of `height` lines below the cursor instead of switching to the
+    /// alternate screen. The last frame stays in the scrollback after exiting.
+    pub fn inline(mut self, height: Option<u16>) -> Self {
+        self.inline_height = height;
+        self
+    }
+
+    /// Request the kitty keyboard protocol, which reports key release and repeat events and
+    /// disambiguates keys such as `ctrl-i` and `tab`. Ignored if the terminal does not support it.
+    pub fn keyboard_enhancement(mut self, keyboard_enhancement: bool) -> Self {
+        self.keyboard_enhancement = keyboard_enhancement;
+        self
+    }
+
+    /// Change the tick rate of the running event loop.
+    pub fn set_tick_rate(&mut self, tick_rate: f64) -> Result<()> {
+        self.tick_rate = validate_rate(tick_rate)?;
+        self.update_rates();
+        Ok(())
+    }
+
+    /// Change the frame rate of the running event loop.
+    pub fn set_frame_rate(&mut self, frame_rate: f64) -> Result<()> {
+        self.frame_rate = validate_rate(frame_rate)?;
+        self.update_rates();
+        Ok(())
+    }
+
+    /// Lower the tick and frame rates, e.g. while the terminal is unfocused, or restore them.
+    pub fn throttle(&mut self, throttled: bool) {
+        self.throttled = throttled;
+        self.update_rates();
+    }
+
+    fn rates(&self) -> Rates {
+        if self.throttled {
+            Rates {
+                tick_rate: self.tick_rate.min(UNFOCUSED_TICK_RATE),
+                frame_rate: self.frame_rate.min(UNFOCUSED_FRAME_RATE),
+            }
+        } else {
+            Rates {
+                tick_rate: self.tick_rate,
+                frame_rate: self.frame_rate,
+            }
+        }
+    }
+
+    fn update_rates(&self) {
+        self.rates_tx.send_if_modified(|rates| {
+            let previous = *rates;
+            *rates = self.rates();
+            *rates != previous
+        });
+    }
+
     pub fn start(&mut self) {
         self.cancel(); // Cancel any existing task
         self.cancellation_token = CancellationToken::new();
+        self.update_rates();
         let event_loop = Self::event_loop(
             self.event_tx.clone(),
             self.cancellation_token.clone(),
-            self.tick_rate,
-            self.frame_rate,
+            self.render_requested.clone(),
+            self.rates_tx.subscribe(),
         );
         self.task = tokio::spawn(async {
             event_loop.await;
@@ -90,41 +216,80 @@ impl Tui {
     }
 
     async fn event_loop(
-        event_tx: UnboundedSender<Event>,
+        event_tx: Sender<Event>,
         cancellation_token: CancellationToken,
-        tick_rate: f64,
-        frame_rate: f64,
+        render_requested: Arc<Notify>,
+        mut rates: watch::Receiver<Rates>,
     ) {
         let mut event_stream = EventStream::new();
+        let Rates {
+            tick_rate,
+            frame_rate,
+        } = *rates.borrow_and_update();
         let mut tick_interval = interval(Duration::from_secs_f64(1.0 / tick_rate));
-        let mut render_interval = interval(Duration::from_secs_f64(1.0 / frame_rate));
+        let mut frame_duration = Duration::from_secs_f64(1.0 / frame_rate);
+        // Renders only happen on request, at most once per frame duration.
+        let mut render_pending = false;
+        let mut next_render = Instant::now();
 
         // if this fails, then it's likely a bug in the calling code
         event_tx
             .send(Event::Init)
+            .await
             .expect("failed to send init event");
         loop {
             let event = tokio::select! {
                 _ = cancellation_token.cancelled() => {
                     break;
                 }
+                Ok(()) = rates.changed() => {
+                    let Rates { tick_rate, frame_rate } = *rates.borrow_and_update();
+                    tick_interval = interval(Duration::from_secs_f64(1.0 / tick_rate));
+                    frame_duration = Duration::from_secs_f64(1.0 / frame_rate);
+                    continue;
+                }
                 _ = tick_interval.tick() => Event::Tick,
-                _ = render_interval.tick() => Event::Render,
+                _ = render_requested.notified(), if !render_pending => {
+                    render_pending = true;
+                    continue;
+                }
+                _ = sleep_until(next_render), if render_pending => {
+                    render_pending = false;
+                    next_render = Instant::now() + frame_duration;
+                    Event::Render
+                }
                 crossterm_event = event_stream.next().fuse() => match crossterm_event {
                     Some(Ok(event)) => match event {
-                        CrosstermEvent::Key(key) if key.kind == KeyEventKind::Press => Event::Key(key),
+                        CrosstermEvent::Key(key) => Event::Key(key),
                         CrosstermEvent::Mouse(mouse) => Event::Mouse(mouse),
                         CrosstermEvent::Resize(x, y) => Event::Resize(x, y),
                         CrosstermEvent::FocusLost => Event::FocusLost,
                         CrosstermEvent::FocusGained => Event::FocusGained,
                         CrosstermEvent::Paste(s) => Event::Paste(s),
-                        _ => continue, // ignore other events
                     }
                     Some(Err(_)) => Event::Error,
                     None => break, // the event stream has stopped and will not produce any more events
                 },
             };
-            if event_tx.send(event).is_err() {
+            // Ticks and renders only matter once the app has caught up, so they are dropped instead
+            // of waiting behind a full queue.
+            let sent = match event {
+                Event::Tick | Event::Render => match event_tx.try_send(event) {
+                    Err(TrySendError::Full(event)) => {
+                        debug!("Event queue is full, dropping {event:?}");
+                        Ok(())
+                    }
+                    Err(TrySendError::Closed(_)) => Err(()),
+                    Ok(()) => Ok(()),
+                },
+                event => {
+                    if event_tx.capacity() == 0 {
+                        warn!("Event queue is full, waiting to send {event:?}");
+                    }
+                    event_tx.send(event).await.map_err(|_| ())
+                }
+            };
+            if sent.is_err() {
                 // the receiver has been dropped, so there's no point in continuing the loop
                 break;
             }
@@ -132,66 +297,103 @@ impl Tui {
         cancellation_token.cancel();
     }
 
-    pub fn stop(&self) -> Result<()> {
+    /// Ask the event loop for an [`Event::Render`]. Requests made within one frame duration of
+    /// the previous render are coalesced into a single event.
+    pub fn request_render(&self) {
+        self.render_requested.notify_one();
+    }
+
+    /// Stops the event loop, giving it [`STOP_TIMEOUT`] to finish before it is aborted.
+    pub async fn stop(&mut self) -> Result<()> {
         self.cancel();
-        let mut counter = 0;
-        while !self.task.is_finished() {
-            std::thread::sleep(Duration::from_millis(1));
-            counter += 1;
-            if counter > 50 {
-                self.task.abort();
-            }
-            if counter > 100 {
-                error!("Failed to abort task in 100 milliseconds for unknown reason");
-                break;
-            }
+        if tokio::time::timeout(STOP_TIMEOUT, &mut self.task)
+            .await
+            .is_err()
+        {
+            warn!("Event loop did not stop within {STOP_TIMEOUT:?}, aborting it");
+            self.task.abort();
         }
         Ok(())
     }
 
     pub fn enter(&mut self) -> Result<()> {
-        crossterm::terminal::enable_raw_mode()?;
-        crossterm::execute!(stdout(), EnterAlternateScreen, cursor::Hide)?;
+        self.entered = true;
+        enable(TerminalMode::Raw)?;
+        match self.inline_height {
+            // Created on every enter, so that the viewport starts below whatever the shell
+            // printed while the app was suspended.
+            Some(height) => {
+                self.terminal = ratatui::Terminal::with_options(
+                    Backend::new(stdout()),
+                    TerminalOptions {
+                        viewport: Viewport::Inline(height),
+                    },
+                )?;
+            }
+            None => enable(TerminalMode::AlternateScreen)?,
+        }
+        enable(TerminalMode::HiddenCursor)?;
         if self.mouse {
-            crossterm::execute!(stdout(), EnableMouseCapture)?;
+            enable(TerminalMode::Mouse)?;
         }
         if self.paste {
-            crossterm::execute!(stdout(), EnableBracketedPaste)?;
+            enable(TerminalMode::Paste)?;
+        }
+        if self.focus {
+            enable(TerminalMode::FocusChange)?;
+        }
+        if self.keyboard_enhancement {
+            if crossterm::terminal::supports_keyboard_enhancement()? {
+                enable(TerminalMode::KeyboardEnhancement)?;
+            } else {
+                warn!("Terminal does not support keyboard enhancement");
+            }
         }
         self.start();
         Ok(())
     }
 
     pub fn exit(&mut self) -> Result<()> {
-        self.stop()?;
-        if crossterm::terminal::is_raw_mode_enabled()? {
-            self.flush()?;
-            if self.paste {
-                crossterm::execute!(stdout(), DisableBracketedPaste)?;
-            }
-            if self.mouse {
-                crossterm::execute!(stdout(), DisableMouseCapture)?;
-            }
-            crossterm::execute!(stdout(), LeaveAlternateScreen, cursor::Show)?;
-            crossterm::terminal::disable_raw_mode()?;
+        let entered = std::mem::take(&mut self.entered);
+        // Not waiting for the event loop here, since this also runs on drop.
+        self.cancel();
+        self.task.abort();
+        // No `flush` here: after a draw it would diff against an empty buffer and blank the screen,
+        // which would erase the inline viewport.
+        if entered && self.inline_height.is_some() {
+            // Continue below the viewport, leaving it in the scrollback.
+            let area = self.get_frame().area();
+            let _ = self.set_cursor_position((0, area.bottom().saturating_sub(1)));
+            let _ = crossterm::execute!(stdout(), Print("\r\n"));
         }
-        Ok(())
+        restore()
     }
 
     pub fn cancel(&self) {
         self.cancellation_token.cancel();
     }
 
+    /// Restores the terminal and stops the process like the default SIGTSTP action does, which is
+    /// replaced by the app's signal handler. Returns once the process is continued; the SIGCONT
+    /// that continues it is then not reported by [`take_self_suspension`].
     pub fn suspend(&mut self) -> Result<()> {
         self.exit()?;
         #[cfg(not(windows))]
-        signal_hook::low_level::raise(signal_hook::consts::signal::SIGTSTP)?;
+        {
+            SUSPENDED_ITSELF.store(true, Ordering::Release);
+            signal_hook::low_level::emulate_default_handler(signal_hook::consts::signal::SIGTSTP)?;
+        }
         Ok(())
     }
 
+    /// Enters the terminal again after [`Tui::suspend`]. If it was never left, e.g. because the
+    /// process was stopped with SIGSTOP from outside, every recorded mode is applied again in case
+    /// the shell reset it in the meantime.
     pub fn resume(&mut self) -> Result<()> {
-        self.enter()?;
-        Ok(())
+        if ENABLED_MODES.load(Ordering::Acquire) != 0 {
+            return reapply();
+        }
+        self.enter()
     }
 
     pub async fn next_event(&mut self) -> Option<Event> {
@@ -199,6 +401,126 @@ impl Tui {
     }
 }
 
+/// A terminal mode set up by [`Tui::enter`]. Each one is recorded in [`ENABLED_MODES`] once it is
+/// enabled, so that [`restore`] undoes exactly what was changed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+enum TerminalMode {
+    Raw = 1 << 0,
+    AlternateScreen = 1 << 1,
+    HiddenCursor = 1 << 2,
+    Mouse = 1 << 3,
+    Paste = 1 << 4,
+    FocusChange = 1 << 5,
+    KeyboardEnhancement = 1 << 6,
+}
+
+impl TerminalMode {
+    /// The reverse of the order in which [`Tui::enter`] enables the modes.
+    const RESTORE_ORDER: [TerminalMode; 7] = [
+        TerminalMode::KeyboardEnhancement,
+        TerminalMode::FocusChange,
+        TerminalMode::Paste,
+        TerminalMode::Mouse,
+        TerminalMode::HiddenCursor,
+        TerminalMode::AlternateScreen,
+        TerminalMode::Raw,
+    ];
+
+    fn enable(self) -> std::io::Result<()> {
+        match self {
+            TerminalMode::Raw => crossterm::terminal::enable_raw_mode(),
+            TerminalMode::AlternateScreen => crossterm::execute!(stdout(), EnterAlternateScreen),
+            TerminalMode::HiddenCursor => crossterm::execute!(stdout(), cursor::Hide),
+            TerminalMode::Mouse => crossterm::execute!(stdout(), EnableMouseCapture),
+            TerminalMode::Paste => crossterm::execute!(stdout(), EnableBracketedPaste),
+            TerminalMode::FocusChange => crossterm::execute!(stdout(), EnableFocusChange),
+            TerminalMode::KeyboardEnhancement => crossterm::execute!(
+                stdout(),
+                PushKeyboardEnhancementFlags(
+                    KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES
+                        | KeyboardEnhancementFlags::REPORT_EVENT_TYPES
+                        | KeyboardEnhancementFlags::REPORT_ALTERNATE_KEYS
+                )
+            ),
+        }
+    }
+
+    fn undo(self) -> std::io::Result<()> {
+        match self {
+            TerminalMode::Raw => crossterm::terminal::disable_raw_mode(),
+            TerminalMode::AlternateScreen => crossterm::execute!(stdout(), LeaveAlternateScreen),
+            TerminalMode::HiddenCursor => crossterm::execute!(stdout(), cursor::Show),
+            TerminalMode::Mouse => crossterm::execute!(stdout(), DisableMouseCapture),
+            TerminalMode::Paste => crossterm::execute!(stdout(), DisableBracketedPaste),
+            TerminalMode::FocusChange => crossterm::execute!(stdout(), DisableFocusChange),
+            TerminalMode::KeyboardEnhancement => {
+                crossterm::execute!(stdout(), PopKeyboardEnhancementFlags)
+            }
+        }
+    }
+}
+
+/// The [`TerminalMode`]s that are currently enabled. Global and lock-free so that the terminal can
+/// be restored from the panic hook, signal handlers and `Drop` alike.
+static ENABLED_MODES: AtomicU8 = AtomicU8::new(0);
+
+/// Set by [`Tui::suspend`] while the process stops itself.
+static SUSPENDED_ITSELF: AtomicBool = AtomicBool::new(false);
+
+/// Whether the process stopped itself with [`Tui::suspend`], which resumes the terminal on its own,
+/// since the last call. For the signal handler to tell that SIGCONT apart from one after the
+/// process was stopped from outside.
+pub fn take_self_suspension() -> bool {
+    SUSPENDED_ITSELF.swap(false, Ordering::AcqRel)
+}
+
+fn enable(mode: TerminalMode) -> Result<()> {
+    mode.enable()?;
+    record(mode);
+    Ok(())
+}
+
+/// Applies the recorded modes again, undoing each one first, so that none is applied twice if the
+/// terminal kept it, e.g. the keyboard enhancement flags, which are a stack.
+fn reapply() -> Result<()> {
+    let modes = ENABLED_MODES.load(Ordering::Acquire);
+    for mode in TerminalMode::RESTORE_ORDER.into_iter().rev() {
+        if modes & mode as u8 != 0 {
+            mode.undo()?;
+            mode.enable()?;
+        }
+    }
+    Ok(())
+}
+
+fn record(mode: TerminalMode) {
+    ENABLED_MODES.fetch_or(mode as u8, Ordering::AcqRel);
+}
+
+fn is_recorded(mode: TerminalMode) -> bool {
+    ENABLED_MODES.load(Ordering::Acquire) & mode as u8 != 0
+}
+
+/// Undoes the terminal modes that are enabled, in reverse order, and forgets them, so that calling
+/// it again does nothing. Never panics; every mode is undone even if undoing another one failed,
+/// and the first error is returned.
+pub fn restore() -> Result<()> {
+    let modes = ENABLED_MODES.swap(0, Ordering::AcqRel);
+    let mut first_error = None;
+    for mode in TerminalMode::RESTORE_ORDER {
+        if modes & mode as u8 != 0 {
+            if let Err(err) = mode.undo() {
+                first_error.get_or_insert(err);
+            }
+        }
+    }
+    match first_error {
+        Some(err) => Err(err.into()),
+        None => Ok(()),
+    }
+}
+
 impl Deref for Tui {
     type Target = ratatui::Terminal<Backend<Stdout>>;
 
@@ -215,6 +537,292 @@ impl DerefMut for Tui {
 
 impl Drop for Tui {
     fn drop(&mut self) {
-        self.exit().unwrap();
+        if self.entered {
+            if let Err(err) = self.exit() {
+                error!("Unable to restore the terminal: {err:?}");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(unix)]
+    use crossterm::event::KeyCode;
+
+    #[test]
+    fn test_validate_rate() {
+        assert!(validate_rate(0.5).is_ok());
+        assert!(validate_rate(0.0).is_err());
+        assert!(validate_rate(-4.0).is_err());
+        assert!(validate_rate(f64::NAN).is_err());
+        assert!(validate_rate(f64::INFINITY).is_err());
+        // Periods too long for a Duration, and too short to be told apart from zero.
+        assert!(validate_rate(1e-20).is_err());
+        assert!(validate_rate(1e10).is_err());
+        assert!(validate_rate(1e-9).is_ok());
+        assert!(validate_rate(1e9).is_ok());
+    }
+
+    #[test]
+    fn test_restore_forgets_modes() {
+        // Raw mode only, since undoing it is a no-op unless crossterm enabled it.
+        record(TerminalMode::Raw);
+        assert!(is_recorded(TerminalMode::Raw));
+        restore().unwrap();
+        assert!(!is_recorded(TerminalMode::Raw));
+        restore().unwrap();
+    }
+
+    /// Tells the test binary that it runs as the child process of a test, see [`spawn_on_pty`].
+    #[cfg(unix)]
+    const CHILD_ENV: &str = "TUI_TEST_CHILD";
+
+    /// Enters, suspends and resumes like the app does, then exits on the first key.
+    #[cfg(unix)]
+    fn suspend_child() -> Result<()> {
+        tokio::runtime::Runtime::new()?.block_on(async {
+            let mut tui = Tui::new()?;
+            tui.enter()?;
+            tui.suspend()?;
+            tui.resume()?;
+            while !matches!(tui.next_event().await, Some(Event::Key(_)) | None) {}
+            tui.exit()
+        })
+    }
+
+    /// Enters with mouse capture, bracketed paste and focus reporting, resumes like the app does
+    /// on SIGCONT once `r` is pressed, then exits on `q`.
+    #[cfg(unix)]
+    fn external_stop_child() -> Result<()> {
+        tokio::runtime::Runtime::new()?.block_on(async {
+            let mut tui = Tui::new()?.mouse(true).paste(true).focus(true);
+            tui.enter()?;
+            for key in ['r', 'q'] {
+                while let Some(event) = tui.next_event().await {
+                    if matches!(event, Event::Key(pressed) if pressed.code == KeyCode::Char(key)) {
+                        break;
+                    }
+                }
+                if key == 'r' {
+                    tui.resume()?;
+                }
+            }
+            tui.exit()
+        })
+    }
+
+    #[cfg(unix)]
+    fn is_raw(fd: &std::os::fd::OwnedFd) -> bool {
+        use std::os::fd::AsRawFd;
+
+        // SAFETY: `termios` is plain data and `fd` is an open terminal.
+        let mut termios = unsafe { std::mem::zeroed::<libc::termios>() };
+        assert_eq!(unsafe { libc::tcgetattr(fd.as_raw_fd(), &mut termios) }, 0);
+        termios.c_lflag & (libc::ICANON | libc::ECHO) == 0
+    }
+
+    /// Turns off raw mode behind the child's back, like a shell does when it takes over the
+    /// terminal.
+    #[cfg(unix)]
+    fn make_cooked(fd: &std::os::fd::OwnedFd) {
+        use std::os::fd::AsRawFd;
+
+        // SAFETY: `termios` is plain data and `fd` is an open terminal.
+        let mut termios = unsafe { std::mem::zeroed::<libc::termios>() };
+        assert_eq!(unsafe { libc::tcgetattr(fd.as_raw_fd(), &mut termios) }, 0);
+        termios.c_lflag |= libc::ICANON | libc::ECHO;
+        assert_eq!(
+            unsafe { libc::tcsetattr(fd.as_raw_fd(), libc::TCSANOW, &termios) },
+            0
+        );
+    }
+
+    #[cfg(unix)]
+    fn wait_until(description: &str, condition: impl Fn() -> bool) {
+        let deadline = std::time::Instant::now() + Duration::from_secs(10);
+        while !condition() {
+            assert!(
+                std::time::Instant::now() < deadline,
+                "timed out: {description}"
+            );
+            std::thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    #[cfg(unix)]
+    fn wait_for_stop(pid: libc::pid_t) {
+        let mut status = 0;
+        // SAFETY: `pid` is our child and `status` is a valid out pointer.
+        assert_eq!(
+            unsafe { libc::waitpid(pid, &mut status, libc::WUNTRACED) },
+            pid
+        );
+        assert!(libc::WIFSTOPPED(status), "child did not stop: {status}");
+    }
+
+    /// A copy of the test binary running only the test `name` on a pseudo terminal.
+    #[cfg(unix)]
+    struct PtyChild {
+        child: std::process::Child,
+        master: std::os::fd::OwnedFd,
+        slave: std::os::fd::OwnedFd,
+        /// Everything the child wrote to the terminal so far.
+        output: Arc<std::sync::Mutex<Vec<u8>>>,
+    }
+
+    #[cfg(unix)]
+    impl PtyChild {
+        fn pid(&self) -> libc::pid_t {
+            self.child.id() as libc::pid_t
+        }
+
+        fn write(&self, input: &[u8]) -> Result<()> {
+            use std::io::Write;
+            std::fs::File::from(self.master.try_clone()?).write_all(input)?;
+            Ok(())
+        }
+
+        /// How often `sequence` occurs in the output.
+        fn count(&self, sequence: &[u8]) -> usize {
+            self.output
+                .lock()
+                .unwrap()
+                .windows(sequence.len())
+                .filter(|window| *window == sequence)
+                .count()
+        }
+    }
+
+    #[cfg(unix)]
+    fn spawn_on_pty(name: &str) -> Result<PtyChild> {
+        use std::{
+            io::Read,
+            os::{
+                fd::{FromRawFd, OwnedFd},
+                unix::process::CommandExt,
+            },
+            process::{Command, Stdio},
+            ptr,
+        };
+
+        let (mut master, mut slave) = (0, 0);
+        // SAFETY: the out pointers are valid and the optional arguments are null.
+        let result = unsafe {
+            libc::openpty(
+                &mut master,
+                &mut slave,
+                ptr::null_mut(),
+                ptr::null(),
+                ptr::null(),
+            )
+        };
+        assert_eq!(result, 0, "openpty failed");
+        // SAFETY: `openpty` returned two new file descriptors that nothing else owns.
+        let (master, slave) =
+            unsafe { (OwnedFd::from_raw_fd(master), OwnedFd::from_raw_fd(slave)) };
+
+        let mut command = Command::new(std::env::current_exe()?);
+        command
+            .args(["--exact", name])
+            .env(CHILD_ENV, "1")
+            .stdin(Stdio::from(slave.try_clone()?))
+            .stdout(Stdio::from(slave.try_clone()?))
+            .stderr(Stdio::from(slave.try_clone()?));
+        // SAFETY: only async-signal-safe calls between fork and exec.
+        unsafe {
+            command.pre_exec(|| {
+                // Make the pseudo terminal the controlling terminal of the child.
+                if libc::setsid() == -1 || libc::ioctl(0, libc::TIOCSCTTY, 0) == -1 {
+                    return Err(std::io::Error::last_os_error());
+                }
+                Ok(())
+            });
+        }
+        let child = command.spawn()?;
+
+        // Also keeps the child from blocking on a full terminal buffer.
+        let output = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let mut reader = std::fs::File::from(master.try_clone()?);
+        let written = output.clone();
+        std::thread::spawn(move || {
+            let mut buffer = [0; 1024];
+            while let Ok(n @ 1..) = reader.read(&mut buffer) {
+                written.lock().unwrap().extend_from_slice(&buffer[..n]);
+            }
+        });
+        Ok(PtyChild {
+            child,
+            master,
+            slave,
+            output,
+        })
+    }
+
+    /// Runs the test binary as a child process on a pseudo terminal and checks the terminal mode
+    /// while it is stopped and after it is continued.
+    #[cfg(unix)]
+    #[test]
+    fn test_suspend_and_resume_restore_terminal_mode() -> Result<()> {
+        if std::env::var_os(CHILD_ENV).is_some() {
+            return suspend_child();
+        }
+        let mut child = spawn_on_pty("tui::tests::test_suspend_and_resume_restore_terminal_mode")?;
+
+        wait_for_stop(child.pid());
+        assert!(
+            !is_raw(&child.slave),
+            "raw mode still enabled while suspended"
+        );
+
+        // SAFETY: `pid` is our stopped child.
+        assert_eq!(unsafe { libc::kill(child.pid(), libc::SIGCONT) }, 0);
+        wait_until("raw mode after resuming", || is_raw(&child.slave));
+
+        child.write(b"q")?;
+        assert!(child.child.wait()?.success());
+        assert!(
+            !is_raw(&child.slave),
+            "raw mode still enabled after exiting"
+        );
+        Ok(())
+    }
+
+    /// Stops the child from outside and resets the terminal while it is stopped, then checks that
+    /// resuming applies every mode again.
+    #[cfg(unix)]
+    #[test]
+    fn test_resume_after_external_stop_reapplies_modes() -> Result<()> {
+        if std::env::var_os(CHILD_ENV).is_some() {
+            return external_stop_child();
+        }
+        let mut child =
+            spawn_on_pty("tui::tests::test_resume_after_external_stop_reapplies_modes")?;
+        let modes: [&[u8]; 3] = [b"\x1b[?1000h", b"\x1b[?2004h", b"\x1b[?1004h"];
+        wait_until("modes enabled", || {
+            is_raw(&child.slave) && modes.iter().all(|mode| child.count(mode) == 1)
+        });
+
+        // SAFETY: `pid` is our child.
+        assert_eq!(unsafe { libc::kill(child.pid(), libc::SIGSTOP) }, 0);
+        wait_for_stop(child.pid());
+        make_cooked(&child.slave);
+        // SAFETY: `pid` is our stopped child.
+        assert_eq!(unsafe { libc::kill(child.pid(), libc::SIGCONT) }, 0);
+
+        // Cooked mode only passes on whole lines.
+        child.write(b"r\n")?;
+        wait_until("modes enabled again", || {
+            is_raw(&child.slave) && modes.iter().all(|mode| child.count(mode) == 2)
+        });
+
+        child.write(b"q")?;
+        assert!(child.child.wait()?.success());
+        assert!(
+            !is_raw(&child.slave),
+            "raw mode still enabled after exiting"
+        );
+        Ok(())
     }
 }